@@ -0,0 +1,69 @@
+use bevy::prelude::*;
+use parry2d::shape::ConvexPolygon;
+
+use crate::world_points;
+
+/// Slack added around each bounding box, so bodies a hair apart still
+/// reach the narrowphase in `collision()` with its contact prediction.
+pub const BROADPHASE_MARGIN: f32 = 1.0;
+
+/// Pairs of colliders whose bounding boxes overlap, each as `(lower, higher)`
+/// entity and sorted, so every pairwise system visits them in the same order.
+#[derive(Debug, Default)]
+pub struct Broadphase {
+    pub pairs: Vec<(Entity, Entity)>,
+}
+
+/// World-space bounding box of a collider, as `(min, max)`.
+pub fn aabb(xform: &Transform, poly: &ConvexPolygon) -> (Vec2, Vec2) {
+    world_points(xform, poly).into_iter().fold(
+        (Vec2::splat(f32::INFINITY), Vec2::splat(f32::NEG_INFINITY)),
+        |(min, max), point| (min.min(point), max.max(point)),
+    )
+}
+
+struct Bounds {
+    entity: Entity,
+    min: Vec2,
+    max: Vec2,
+}
+
+fn bounds(entity: Entity, xform: &Transform, poly: &ConvexPolygon) -> Bounds {
+    let (min, max) = aabb(xform, poly);
+    Bounds {
+        entity,
+        min: min - Vec2::splat(BROADPHASE_MARGIN),
+        max: max + Vec2::splat(BROADPHASE_MARGIN),
+    }
+}
+
+impl Broadphase {
+    /// Sweep and prune along x over every collider's bounding box.
+    pub fn update<'a>(
+        &mut self,
+        colliders: impl Iterator<Item = (Entity, &'a Transform, &'a ConvexPolygon)>,
+    ) {
+        let mut all: Vec<_> = colliders
+            .map(|(entity, xform, poly)| bounds(entity, xform, poly))
+            .collect();
+        all.sort_by(|a, b| a.min.x.partial_cmp(&b.min.x).expect("finite bounds"));
+
+        self.pairs.clear();
+        for (index, a) in all.iter().enumerate() {
+            for b in all[index + 1..].iter() {
+                if b.min.x > a.max.x {
+                    break;
+                }
+
+                if a.min.y <= b.max.y && b.min.y <= a.max.y {
+                    self.pairs.push(if a.entity < b.entity {
+                        (a.entity, b.entity)
+                    } else {
+                        (b.entity, a.entity)
+                    });
+                }
+            }
+        }
+        self.pairs.sort();
+    }
+}
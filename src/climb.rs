@@ -0,0 +1,94 @@
+use bevy::prelude::*;
+
+use crate::{CollisionLayers, Contacts, Ladder, Layer, Momentum, Player, DELTA};
+
+/// How far the player's center can be from a ladder's and still grab it.
+pub const LADDER_GRAB_DISTANCE: f32 = 20.0;
+
+/// Climbing speed, in units of `Velocity`.
+pub const CLIMB_SPEED: f32 = 1.0;
+
+pub const CLIMB_UP_KEY: KeyCode = KeyCode::W;
+pub const CLIMB_DOWN_KEY: KeyCode = KeyCode::S;
+
+/// The ladder the player is holding on to, if any.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Climbing(pub Option<Entity>);
+
+/*
+The player grabs a ladder they're touching by pressing up or down near its
+middle, and snaps onto it. On the ladder there's no gravity: up and down
+climb, and letting go of both holds still. Walking left or right steps off,
+as does climbing off either end.
+
+Ladder tops are one-way platforms, so from above they can be stood on.
+While climbing, the player stops noticing ladders, which makes the contact
+non-solid and lets them climb down through the top.
+*/
+pub fn climb(
+    keys: Res<Input<KeyCode>>,
+    contacts: Res<Contacts>,
+    ladders: Query<(Entity, &Transform), With<Ladder>>,
+    mut players: Query<
+        (
+            Entity,
+            &Transform,
+            &mut Climbing,
+            &mut CollisionLayers,
+            &mut Momentum,
+        ),
+        With<Player>,
+    >,
+) {
+    let up = keys.pressed(CLIMB_UP_KEY);
+    let down = keys.pressed(CLIMB_DOWN_KEY);
+    let sideways = keys.pressed(KeyCode::A) || keys.pressed(KeyCode::D);
+
+    for (player, xform, mut climbing, mut layers, mut momentum) in players.iter_mut() {
+        if let Some(ladder) = climbing.0 {
+            if sideways || !contacts.contains(player, ladder) {
+                climbing.0 = None;
+                momentum.0 = Vec2::ZERO;
+            }
+        }
+
+        if climbing.0.is_none() && (up || down) && !sideways {
+            climbing.0 = ladders
+                .iter()
+                .filter(|(ladder, _)| contacts.contains(player, *ladder))
+                .map(|(ladder, ladder_xform)| {
+                    (
+                        ladder,
+                        (ladder_xform.translation.x - xform.translation.x).abs(),
+                    )
+                })
+                .filter(|(_, distance)| *distance <= LADDER_GRAB_DISTANCE)
+                .min_by(|(_, a), (_, b)| a.partial_cmp(b).expect("finite distance"))
+                .map(|(ladder, _)| ladder);
+        }
+
+        let ladder_xform = match climbing.0.and_then(|ladder| ladders.get(ladder).ok()) {
+            Some((_, ladder_xform)) => ladder_xform,
+            None => {
+                layers.mask |= Layer::Ladder.bit();
+                continue;
+            }
+        };
+        layers.mask &= !Layer::Ladder.bit();
+
+        let climb = if up {
+            CLIMB_SPEED
+        } else if down {
+            -CLIMB_SPEED
+        } else {
+            0.0
+        };
+
+        // replaces whatever gravity added this tick, and lines up with the
+        // ladder's middle by the end of it
+        momentum.0 = Vec2::new(
+            (ladder_xform.translation.x - xform.translation.x) / DELTA,
+            climb,
+        );
+    }
+}
@@ -0,0 +1,192 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use bevy::prelude::*;
+use parry2d::shape::ConvexPolygon;
+
+use crate::{collision, BevyCollision, Broadphase, CollisionLayers, IgnoreContacts, Momentum};
+
+#[derive(Debug, Clone)]
+pub struct Contact {
+    /// `normal1` points out of the lower entity of the pair.
+    pub collision: BevyCollision,
+    /// Whether the bodies push each other apart, rather than one of them
+    /// only noticing the overlap.
+    pub solid: bool,
+}
+
+/// Every touching pair of colliders, keyed `(lower, higher)` entity.
+///
+/// Regenerated at the start of each tick and once more after integration,
+/// for the `Solver` to work from.
+#[derive(Debug, Default)]
+pub struct Contacts {
+    pub pairs: BTreeMap<(Entity, Entity), Contact>,
+    /// How far the solver has pushed each body since `pairs` was generated.
+    /// A pair's overlap is its `dist` adjusted by how far its bodies have
+    /// since moved along the normal.
+    pub offsets: BTreeMap<Entity, Vec2>,
+    /// Pairs touching at the start of the last tick, to diff events against.
+    pub(crate) previous: BTreeSet<(Entity, Entity)>,
+}
+
+impl Contacts {
+    /// Contacts between bodies that push each other apart, in entity order.
+    pub fn solid(&self) -> impl Iterator<Item = (Entity, Entity, &BevyCollision)> {
+        self.pairs
+            .iter()
+            .filter(|(_, contact)| contact.solid)
+            .map(|(&(a, b), contact)| (a, b, &contact.collision))
+    }
+
+    /// Everything touching `entity`, solid or not.
+    pub fn touching(&self, entity: Entity) -> impl Iterator<Item = Entity> + '_ {
+        self.pairs.keys().filter_map(move |&(a, b)| {
+            if a == entity {
+                Some(b)
+            } else if b == entity {
+                Some(a)
+            } else {
+                None
+            }
+        })
+    }
+
+    /// How far `entity` has been pushed since contacts were generated.
+    pub fn offset(&self, entity: Entity) -> Vec2 {
+        self.offsets.get(&entity).copied().unwrap_or(Vec2::ZERO)
+    }
+
+    pub fn contains(&self, a: Entity, b: Entity) -> bool {
+        self.pairs.contains_key(&(a.min(b), a.max(b)))
+    }
+
+    /// The normal of a solid contact, pointing out of `from` towards `to`.
+    pub fn normal(&self, from: Entity, to: Entity) -> Option<Vec2> {
+        let contact = self.pairs.get(&(from.min(to), from.max(to)))?;
+        if !contact.solid {
+            return None;
+        }
+
+        Some(if from < to {
+            contact.collision.normal1
+        } else {
+            contact.collision.normal2
+        })
+    }
+}
+
+/// Sent once per tick for every touching pair, from the start-of-tick pass.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CollisionEvent {
+    Started(Entity, Entity),
+    Persisted(Entity, Entity),
+    Ended(Entity, Entity),
+}
+
+/// Deepest a body can sink into a one-way platform in a tick and still land
+/// on it: a little more than a tick's fall at `TERMINAL_VELOCITY`.
+pub const ONE_WAY_DEPTH: f32 = 8.0;
+
+/// A platform bodies pass through from below and from the sides, and stand
+/// on from above. Passing through, the contact is there but isn't solid.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct OneWay;
+
+/// Whether a body touching a one-way platform, with `normal` pointing from
+/// the body into the platform, is landing on it rather than passing through.
+fn lands_on(normal: Vec2, dist: f32, momentum: Option<&Momentum>) -> bool {
+    normal.y < 0.0
+        && -dist <= ONE_WAY_DEPTH
+        && momentum.map_or(true, |momentum| momentum.0.y <= 0.0)
+}
+
+type Colliders<'w> = Query<
+    'w,
+    (
+        Entity,
+        &'static Transform,
+        &'static ConvexPolygon,
+        &'static CollisionLayers,
+        Option<&'static IgnoreContacts>,
+        Option<&'static OneWay>,
+        Option<&'static Momentum>,
+    ),
+>;
+
+fn ignores(ignore: Option<&IgnoreContacts>, entity: Entity) -> bool {
+    ignore.map_or(false, |ignore| ignore.ignores(entity))
+}
+
+fn generate(broadphase: &mut Broadphase, contacts: &mut Contacts, q: &Colliders) {
+    broadphase.update(
+        q.iter()
+            .map(|(entity, xform, poly, _, _, _, _)| (entity, xform, poly)),
+    );
+
+    contacts.pairs.clear();
+    contacts.offsets.clear();
+    for &(entity_a, entity_b) in broadphase.pairs.iter() {
+        if let (
+            Ok((_, xform_a, poly_a, layers_a, ignore_a, one_way_a, momentum_a)),
+            Ok((_, xform_b, poly_b, layers_b, ignore_b, one_way_b, momentum_b)),
+        ) = (q.get(entity_a), q.get(entity_b))
+        {
+            if ignores(ignore_a, entity_b) || ignores(ignore_b, entity_a) {
+                continue;
+            }
+
+            let a_notices = layers_a.notices(layers_b);
+            let b_notices = layers_b.notices(layers_a);
+            if !a_notices && !b_notices {
+                continue;
+            }
+
+            if let Some(collision) = collision(poly_a, xform_a, poly_b, xform_b) {
+                let passes_b =
+                    one_way_b.is_some() && !lands_on(collision.normal1, collision.dist, momentum_a);
+                let passes_a =
+                    one_way_a.is_some() && !lands_on(collision.normal2, collision.dist, momentum_b);
+
+                contacts.pairs.insert(
+                    (entity_a, entity_b),
+                    Contact {
+                        collision,
+                        solid: a_notices && b_notices && !passes_a && !passes_b,
+                    },
+                );
+            }
+        }
+    }
+}
+
+pub fn update_contacts(
+    mut broadphase: ResMut<Broadphase>,
+    mut contacts: ResMut<Contacts>,
+    mut events: EventWriter<CollisionEvent>,
+    q: Colliders,
+) {
+    generate(&mut broadphase, &mut contacts, &q);
+
+    let touching: BTreeSet<_> = contacts.pairs.keys().copied().collect();
+    for &(a, b) in touching.iter() {
+        events.send(if contacts.previous.contains(&(a, b)) {
+            CollisionEvent::Persisted(a, b)
+        } else {
+            CollisionEvent::Started(a, b)
+        });
+    }
+    for &(a, b) in contacts.previous.difference(&touching) {
+        events.send(CollisionEvent::Ended(a, b));
+    }
+
+    contacts.previous = touching;
+}
+
+/// Regenerates contacts after integration, for `process_collisions`.
+pub fn refresh_contacts(
+    mut broadphase: ResMut<Broadphase>,
+    mut contacts: ResMut<Contacts>,
+    q: Colliders,
+) {
+    generate(&mut broadphase, &mut contacts, &q);
+}
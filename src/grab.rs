@@ -0,0 +1,91 @@
+use bevy::prelude::*;
+use parry2d::shape::ConvexPolygon;
+
+use crate::{aabb, Climbing, Contacts, Crate, Player, Velocity};
+
+/// Key held to take hold of the crate the player is standing beside.
+pub const GRAB_KEY: KeyCode = KeyCode::LShift;
+
+/// Walking speed while holding a crate, as a fraction of the usual.
+pub const GRAB_SPEED: f32 = 0.5;
+
+/// How far a held crate can get from the player before it's let go.
+pub const GRAB_REACH: f32 = 2.0;
+
+/// The crate the player is holding on to, if any.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Grabbing(pub Option<Entity>);
+
+/// The distance between two bounding boxes, zero when they overlap.
+fn gap((min_a, max_a): (Vec2, Vec2), (min_b, max_b): (Vec2, Vec2)) -> f32 {
+    (min_b - max_a).max(min_a - max_b).max(Vec2::ZERO).length()
+}
+
+/*
+Holding the grab key takes hold of the nearest crate the player is touching
+side-on, so it can be pulled as well as pushed. Releasing the key lets go,
+and so does the crate getting out of reach, e.g. when a step it rests on
+carries it off. Climbing a ladder takes both hands.
+*/
+pub fn grab(
+    keys: Res<Input<KeyCode>>,
+    contacts: Res<Contacts>,
+    crates: Query<(&Transform, &ConvexPolygon), With<Crate>>,
+    mut players: Query<
+        (Entity, &Transform, &ConvexPolygon, &Climbing, &mut Grabbing),
+        With<Player>,
+    >,
+) {
+    let held = keys.pressed(GRAB_KEY);
+
+    for (player, xform, poly, climbing, mut grabbing) in players.iter_mut() {
+        let can_hold = held && climbing.0.is_none();
+        let bounds = aabb(xform, poly);
+
+        if let Some(crate_) = grabbing.0 {
+            let in_reach = crates
+                .get(crate_)
+                .map_or(false, |(crate_xform, crate_poly)| {
+                    gap(bounds, aabb(crate_xform, crate_poly)) <= GRAB_REACH
+                });
+            if !can_hold || !in_reach {
+                grabbing.0 = None;
+            }
+        }
+
+        if grabbing.0.is_none() && can_hold {
+            grabbing.0 = contacts
+                .touching(player)
+                .filter_map(|other| {
+                    let (crate_xform, _) = crates.get(other).ok()?;
+                    let normal = contacts.normal(player, other)?;
+                    // beside it, rather than standing on or under it
+                    if normal.x.abs() <= normal.y.abs() {
+                        return None;
+                    }
+
+                    Some((
+                        other,
+                        (crate_xform.translation - xform.translation).length(),
+                    ))
+                })
+                .min_by(|(_, a), (_, b)| a.partial_cmp(b).expect("finite distance"))
+                .map(|(crate_, _)| crate_);
+        }
+    }
+}
+
+/// A held crate keeps pace with the player's walking, so it follows when
+/// pulled. Only sideways: it still falls and rides on its own.
+pub fn drag(
+    players: Query<(&Grabbing, &Velocity), With<Player>>,
+    mut crates: Query<&mut Velocity, (With<Crate>, Without<Player>)>,
+) {
+    for (grabbing, velocity) in players.iter() {
+        if let Some(crate_) = grabbing.0 {
+            if let Ok(mut crate_velocity) = crates.get_mut(crate_) {
+                crate_velocity.0.x += velocity.0.x;
+            }
+        }
+    }
+}
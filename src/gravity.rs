@@ -0,0 +1,88 @@
+use bevy::prelude::*;
+
+use crate::{Support, Velocity};
+
+/// Speed gained falling for one tick.
+pub const GRAVITY: f32 = 0.15;
+
+/// Fastest a body can fall.
+pub const TERMINAL_VELOCITY: f32 = 6.0;
+
+/// Upward speed at the start of a jump; high enough to clear one escalator
+/// step with room to spare.
+pub const JUMP_SPEED: f32 = 4.0;
+
+pub const JUMP_KEY: KeyCode = KeyCode::Space;
+
+/// Ticks after walking off an edge during which a jump still counts.
+pub const COYOTE_TICKS: u32 = 6;
+
+/// Ticks before landing during which a jump press is remembered.
+pub const JUMP_BUFFER_TICKS: u32 = 6;
+
+/// The part of a body's velocity that carries over from tick to tick.
+///
+/// `Velocity` is rebuilt every tick from this plus walking and being carried;
+/// gravity and jumps act on `Momentum`, and contacts stop it.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Momentum(pub Vec2);
+
+/// Jump timing for the player, counted in ticks.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Jump {
+    pub since_grounded: u32,
+    pub since_pressed: u32,
+    /// Whether `JUMP_KEY` was down last tick, to spot new presses.
+    pub held: bool,
+}
+
+impl Default for Jump {
+    fn default() -> Self {
+        Jump {
+            since_grounded: u32::MAX,
+            since_pressed: u32::MAX,
+            held: false,
+        }
+    }
+}
+
+pub fn gravity(mut bodies: Query<&mut Momentum>) {
+    for mut momentum in bodies.iter_mut() {
+        momentum.0.y = (momentum.0.y - GRAVITY).max(-TERMINAL_VELOCITY);
+    }
+}
+
+/// Jumps when the player is, or was until a moment ago, standing on
+/// something, and pressed `JUMP_KEY` now or a moment ago.
+pub fn jump(keys: Res<Input<KeyCode>>, mut players: Query<(&Support, &mut Jump, &mut Momentum)>) {
+    // edge-triggered off our own state, like levers
+    let pressed = keys.pressed(JUMP_KEY);
+
+    for (support, mut jump, mut momentum) in players.iter_mut() {
+        jump.since_grounded = if support.0.is_some() {
+            0
+        } else {
+            jump.since_grounded.saturating_add(1)
+        };
+        jump.since_pressed = if pressed && !jump.held {
+            0
+        } else {
+            jump.since_pressed.saturating_add(1)
+        };
+        jump.held = pressed;
+
+        if jump.since_grounded <= COYOTE_TICKS && jump.since_pressed <= JUMP_BUFFER_TICKS {
+            momentum.0.y = JUMP_SPEED;
+
+            // spend both, so one press gives one jump
+            jump.since_grounded = u32::MAX;
+            jump.since_pressed = u32::MAX;
+        }
+    }
+}
+
+pub fn apply_momentum(mut bodies: Query<(&Momentum, &mut Velocity)>) {
+    for (momentum, mut velocity) in bodies.iter_mut() {
+        velocity.0 += momentum.0;
+    }
+}
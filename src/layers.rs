@@ -0,0 +1,88 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// A collision layer, as named in level files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Layer {
+    Ground,
+    Escalator,
+    Step,
+    Crate,
+    Player,
+    Ladder,
+    Sensor,
+}
+
+impl Layer {
+    pub fn bit(self) -> u32 {
+        1 << self as u32
+    }
+
+    pub fn bits(layers: &[Layer]) -> u32 {
+        layers.iter().fold(0, |bits, layer| bits | layer.bit())
+    }
+}
+
+/// The layers a collider is on (`groups`) and the layers it notices (`mask`).
+///
+/// A pair is reported in `Contacts` when either side notices the other, and
+/// pushes apart only when both do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CollisionLayers {
+    pub groups: u32,
+    pub mask: u32,
+}
+
+const SOLID: u32 = 1 << Layer::Ground as u32
+    | 1 << Layer::Escalator as u32
+    | 1 << Layer::Step as u32
+    | 1 << Layer::Crate as u32
+    | 1 << Layer::Player as u32;
+
+impl CollisionLayers {
+    pub const GROUND: CollisionLayers = CollisionLayers {
+        groups: 1 << Layer::Ground as u32,
+        mask: SOLID,
+    };
+    pub const ESCALATOR: CollisionLayers = CollisionLayers {
+        groups: 1 << Layer::Escalator as u32,
+        mask: SOLID,
+    };
+    /// Steps don't notice each other.
+    pub const STEP: CollisionLayers = CollisionLayers {
+        groups: 1 << Layer::Step as u32,
+        mask: SOLID & !(1 << Layer::Step as u32),
+    };
+    pub const CRATE: CollisionLayers = CollisionLayers {
+        groups: 1 << Layer::Crate as u32,
+        mask: SOLID,
+    };
+    /// The player stands on ladder tops, except while climbing.
+    pub const PLAYER: CollisionLayers = CollisionLayers {
+        groups: 1 << Layer::Player as u32,
+        mask: SOLID | 1 << Layer::Ladder as u32,
+    };
+    pub const LADDER: CollisionLayers = CollisionLayers {
+        groups: 1 << Layer::Ladder as u32,
+        mask: 1 << Layer::Player as u32,
+    };
+    /// Pressure plates, levers, exits and target pads: they notice crates
+    /// and the player, but nothing notices them back.
+    pub const SENSOR: CollisionLayers = CollisionLayers {
+        groups: 1 << Layer::Sensor as u32,
+        mask: 1 << Layer::Crate as u32 | 1 << Layer::Player as u32,
+    };
+
+    pub fn notices(&self, other: &CollisionLayers) -> bool {
+        self.mask & other.groups != 0
+    }
+}
+
+/// Specific entities this collider never touches, whatever their layers.
+pub struct IgnoreContacts(pub Vec<Entity>);
+
+impl IgnoreContacts {
+    pub fn ignores(&self, entity: Entity) -> bool {
+        self.0.contains(&entity)
+    }
+}
@@ -0,0 +1,635 @@
+use std::{collections::HashMap, fmt};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    spawn_conveyor, spawn_crate, spawn_door, spawn_escalator, spawn_exit, spawn_ground,
+    spawn_ladder, spawn_lever, spawn_moving_platform, spawn_platform, spawn_player,
+    spawn_pressure_plate, spawn_ramp, spawn_step, spawn_target_pad, steps, CollisionLayers,
+    Conveyor, Escalator, EscalatorState, Facing, InLevel, Layer, Mass, Path, PathMode,
+    PhysicsMaterial, Powered, Travel,
+};
+
+/// A puzzle layout, as authored in a `.ron` level file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Level {
+    pub entities: Vec<LevelEntity>,
+    #[serde(default)]
+    pub wires: Vec<Wire>,
+}
+
+/// Connects the pressure plate or lever named `from` to the escalator,
+/// conveyor or door named `to`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Wire {
+    pub from: String,
+    pub to: String,
+}
+
+/// One placed entity. `position` is always the center of the entity, and
+/// `rotation`, where there is one, is counterclockwise in degrees about it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum LevelEntity {
+    /// `collides_with` limits what the ground stops, e.g. `Some([Player])`
+    /// for a barrier that crates fall through. `material` makes e.g. an ice
+    /// floor.
+    Ground {
+        position: Vec2,
+        size: Vec2,
+        #[serde(default)]
+        rotation: f32,
+        #[serde(default)]
+        collides_with: Option<Vec<Layer>>,
+        #[serde(default)]
+        material: Option<PhysicsMaterial>,
+    },
+    /// Ground that bodies pass up through and land on top of.
+    Platform {
+        position: Vec2,
+        size: Vec2,
+        #[serde(default)]
+        material: Option<PhysicsMaterial>,
+    },
+    /// Ground whose surface carries what rests on it towards `facing`.
+    Conveyor {
+        position: Vec2,
+        size: Vec2,
+        #[serde(default)]
+        facing: Facing,
+        #[serde(default = "default_speed")]
+        speed: f32,
+        #[serde(default)]
+        state: EscalatorState,
+        #[serde(default)]
+        name: Option<String>,
+        #[serde(default)]
+        material: Option<PhysicsMaterial>,
+    },
+    /// A box of ground travelling through `waypoints`, starting at the first.
+    MovingPlatform {
+        size: Vec2,
+        waypoints: Vec<Vec2>,
+        #[serde(default = "default_speed")]
+        speed: f32,
+        #[serde(default)]
+        mode: PathMode,
+        #[serde(default)]
+        material: Option<PhysicsMaterial>,
+    },
+    /// A wedge of ground rising towards `facing`.
+    Ramp {
+        position: Vec2,
+        size: Vec2,
+        #[serde(default)]
+        facing: Facing,
+        #[serde(default)]
+        material: Option<PhysicsMaterial>,
+    },
+    Escalator {
+        position: Vec2,
+        length: f32,
+        #[serde(default)]
+        rotation: f32,
+        step_size: f32,
+        #[serde(default)]
+        facing: Facing,
+        #[serde(default)]
+        travel: Travel,
+        #[serde(default = "default_speed")]
+        speed: f32,
+        #[serde(default)]
+        state: EscalatorState,
+        #[serde(default)]
+        name: Option<String>,
+        /// Material for every step, e.g. sticky steps that hold crates.
+        #[serde(default)]
+        step_material: Option<PhysicsMaterial>,
+    },
+    /// Climbed by the player, who can also stand on top of it.
+    Ladder {
+        position: Vec2,
+        size: Vec2,
+    },
+    /// `collides_with` limits what the crate rests on or pushes, e.g.
+    /// `Some([Ground])` for a ghost crate the player walks through. `mass`
+    /// overrides the default of 1, the player's mass.
+    Crate {
+        position: Vec2,
+        size: Vec2,
+        #[serde(default)]
+        rotation: f32,
+        #[serde(default)]
+        collides_with: Option<Vec<Layer>>,
+        #[serde(default)]
+        mass: Option<f32>,
+        #[serde(default)]
+        material: Option<PhysicsMaterial>,
+    },
+    Player {
+        position: Vec2,
+        size: Vec2,
+        #[serde(default)]
+        material: Option<PhysicsMaterial>,
+    },
+    PressurePlate {
+        position: Vec2,
+        size: Vec2,
+        #[serde(default)]
+        name: Option<String>,
+    },
+    Lever {
+        position: Vec2,
+        size: Vec2,
+        #[serde(default)]
+        name: Option<String>,
+    },
+    Door {
+        position: Vec2,
+        size: Vec2,
+        #[serde(default)]
+        name: Option<String>,
+    },
+    Exit {
+        position: Vec2,
+        size: Vec2,
+    },
+    TargetPad {
+        position: Vec2,
+        size: Vec2,
+    },
+}
+
+impl LevelEntity {
+    /// The name wires refer to this entity by, if it has one.
+    pub fn name(&self) -> Option<&str> {
+        match self {
+            LevelEntity::Escalator { name, .. }
+            | LevelEntity::Conveyor { name, .. }
+            | LevelEntity::PressurePlate { name, .. }
+            | LevelEntity::Lever { name, .. }
+            | LevelEntity::Door { name, .. } => name.as_deref(),
+            _ => None,
+        }
+    }
+
+    /// Whether a wire can run from this entity.
+    fn is_switch(&self) -> bool {
+        matches!(
+            self,
+            LevelEntity::PressurePlate { .. } | LevelEntity::Lever { .. }
+        )
+    }
+
+    /// Whether a wire can run to this entity.
+    fn is_powerable(&self) -> bool {
+        matches!(
+            self,
+            LevelEntity::Escalator { .. } | LevelEntity::Conveyor { .. } | LevelEntity::Door { .. }
+        )
+    }
+}
+
+fn default_speed() -> f32 {
+    1.0
+}
+
+/// Handles used to draw each kind of level entity.
+#[derive(Default)]
+pub struct LevelMaterials {
+    pub escalator: Handle<TextureAtlas>,
+    pub step: Handle<ColorMaterial>,
+    pub ground: Handle<ColorMaterial>,
+    pub ladder: Handle<ColorMaterial>,
+    pub crate_: Handle<ColorMaterial>,
+    pub player: Handle<ColorMaterial>,
+    pub pressure_plate: Handle<ColorMaterial>,
+    pub lever: Handle<ColorMaterial>,
+    pub door: Handle<ColorMaterial>,
+    pub exit: Handle<ColorMaterial>,
+    pub target_pad: Handle<ColorMaterial>,
+}
+
+/// Why a level file couldn't be loaded.
+#[derive(Debug)]
+pub enum LevelError {
+    Parse(ron::Error),
+    /// An escalator's `length` or `step_size` isn't positive and finite, or
+    /// its steps are longer than the escalator.
+    BadEscalator {
+        length: f32,
+        step_size: f32,
+    },
+    /// An escalator's or conveyor's `speed` isn't positive and finite.
+    /// Authoring one stopped is done with its `state`.
+    BadSpeed(f32),
+    /// A crate's `mass` is zero, negative or NaN.
+    NonPositiveMass(f32),
+    /// A wire names an entity that isn't in the level.
+    UnknownWire(String),
+    /// Two entities share a name, so wires to it would be ambiguous.
+    DuplicateName(String),
+    /// A wire doesn't run from a switch to something switches can power.
+    BadWire {
+        from: String,
+        to: String,
+    },
+}
+
+impl fmt::Display for LevelError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LevelError::Parse(error) => write!(f, "{}", error),
+            LevelError::BadEscalator { length, step_size } => write!(
+                f,
+                "escalator of length {} can't have steps of size {}",
+                length, step_size
+            ),
+            LevelError::BadSpeed(speed) => {
+                write!(f, "speed must be positive and finite, not {}", speed)
+            }
+            LevelError::NonPositiveMass(mass) => {
+                write!(f, "crate mass must be positive, not {}", mass)
+            }
+            LevelError::UnknownWire(name) => write!(f, "wire names unknown entity `{}`", name),
+            LevelError::DuplicateName(name) => write!(f, "more than one entity is named `{}`", name),
+            LevelError::BadWire { from, to } => write!(
+                f,
+                "wire from `{}` to `{}` must run from a pressure plate or lever to an escalator, conveyor or door",
+                from, to
+            ),
+        }
+    }
+}
+
+impl std::error::Error for LevelError {}
+
+impl From<ron::Error> for LevelError {
+    fn from(error: ron::Error) -> Self {
+        LevelError::Parse(error)
+    }
+}
+
+fn positive(value: f32) -> bool {
+    value.is_finite() && value > 0.0
+}
+
+impl Level {
+    pub fn from_ron(source: &str) -> Result<Level, LevelError> {
+        let level: Level = ron::from_str(source)?;
+
+        for entity in level.entities.iter() {
+            match *entity {
+                LevelEntity::Escalator {
+                    length,
+                    step_size,
+                    speed,
+                    ..
+                } => {
+                    if !positive(length) || !positive(step_size) || step_size > length {
+                        return Err(LevelError::BadEscalator { length, step_size });
+                    }
+                    if !positive(speed) {
+                        return Err(LevelError::BadSpeed(speed));
+                    }
+                }
+                LevelEntity::Conveyor { speed, .. } if !positive(speed) => {
+                    return Err(LevelError::BadSpeed(speed));
+                }
+                // an infinite mass is allowed, and never moves
+                LevelEntity::Crate {
+                    mass: Some(mass), ..
+                } if mass.is_nan() || mass <= 0.0 => {
+                    return Err(LevelError::NonPositiveMass(mass));
+                }
+                _ => {}
+            }
+        }
+
+        Ok(level)
+    }
+}
+
+fn t(position: Vec2) -> Transform {
+    Transform::from_translation(position.extend(0.0))
+}
+
+fn rotated(position: Vec2, degrees: f32) -> Transform {
+    Transform {
+        rotation: Quat::from_rotation_z(degrees.to_radians()),
+        ..t(position)
+    }
+}
+
+fn register_name(names: &mut HashMap<String, Entity>, name: &Option<String>, entity: Entity) {
+    if let Some(name) = name {
+        names.insert(name.clone(), entity);
+    }
+}
+
+/// Checks that names are unique and that every wire runs from a switch to
+/// something it can power.
+fn check_wires(level: &Level) -> Result<(), LevelError> {
+    let mut named = HashMap::new();
+    for entity in level.entities.iter() {
+        if let Some(name) = entity.name() {
+            if named.insert(name, entity).is_some() {
+                return Err(LevelError::DuplicateName(name.to_string()));
+            }
+        }
+    }
+
+    let find = |name: &String| {
+        named
+            .get(name.as_str())
+            .ok_or_else(|| LevelError::UnknownWire(name.clone()))
+    };
+    for wire in level.wires.iter() {
+        if !find(&wire.from)?.is_switch() || !find(&wire.to)?.is_powerable() {
+            return Err(LevelError::BadWire {
+                from: wire.from.clone(),
+                to: wire.to.clone(),
+            });
+        }
+    }
+
+    Ok(())
+}
+
+fn with_material(commands: &mut Commands, entity: Entity, material: Option<PhysicsMaterial>) {
+    if let Some(material) = material {
+        commands.entity(entity).insert(material);
+    }
+}
+
+fn collide_with(
+    commands: &mut Commands,
+    entity: Entity,
+    layers: CollisionLayers,
+    collides_with: &Option<Vec<Layer>>,
+) {
+    if let Some(collides_with) = collides_with {
+        commands.entity(entity).insert(CollisionLayers {
+            mask: Layer::bits(collides_with),
+            ..layers
+        });
+    }
+}
+
+/// Spawns every entity in `level`, in file order, then connects its wires.
+/// Everything spawned is tagged `InLevel` so the level can be torn down again.
+///
+/// Fails without spawning anything if the wires don't check out.
+pub fn spawn_level(
+    commands: &mut Commands,
+    level: &Level,
+    materials: &LevelMaterials,
+) -> Result<(), LevelError> {
+    check_wires(level)?;
+
+    let mut names = HashMap::new();
+    let mut spawned = vec![];
+
+    for entity in level.entities.iter() {
+        match *entity {
+            LevelEntity::Ground {
+                position,
+                size,
+                rotation,
+                ref collides_with,
+                material,
+            } => {
+                let ground = spawn_ground(
+                    commands,
+                    materials.ground.clone_weak(),
+                    size,
+                    rotated(position, rotation),
+                );
+                collide_with(commands, ground, CollisionLayers::GROUND, collides_with);
+                with_material(commands, ground, material);
+                spawned.push(ground);
+            }
+            LevelEntity::Platform {
+                position,
+                size,
+                material,
+            } => {
+                let platform =
+                    spawn_platform(commands, materials.ground.clone_weak(), size, t(position));
+                with_material(commands, platform, material);
+                spawned.push(platform);
+            }
+            LevelEntity::Conveyor {
+                position,
+                size,
+                facing,
+                speed,
+                state,
+                ref name,
+                material,
+            } => {
+                let conveyor = spawn_conveyor(
+                    commands,
+                    materials.ground.clone_weak(),
+                    size,
+                    t(position),
+                    Conveyor {
+                        facing,
+                        speed,
+                        state,
+                    },
+                );
+                with_material(commands, conveyor, material);
+                register_name(&mut names, name, conveyor);
+                spawned.push(conveyor);
+            }
+            LevelEntity::MovingPlatform {
+                size,
+                ref waypoints,
+                speed,
+                mode,
+                material,
+            } => {
+                let platform = spawn_moving_platform(
+                    commands,
+                    materials.ground.clone_weak(),
+                    size,
+                    Path {
+                        waypoints: waypoints.clone(),
+                        speed,
+                        mode,
+                    },
+                );
+                with_material(commands, platform, material);
+                spawned.push(platform);
+            }
+            LevelEntity::Ramp {
+                position,
+                size,
+                facing,
+                material,
+            } => {
+                let ramp = spawn_ramp(
+                    commands,
+                    materials.ground.clone_weak(),
+                    size,
+                    facing,
+                    t(position),
+                );
+                with_material(commands, ramp, material);
+                spawned.push(ramp);
+            }
+            LevelEntity::Escalator {
+                position,
+                length,
+                rotation,
+                step_size,
+                facing,
+                travel,
+                speed,
+                state,
+                ref name,
+                step_material,
+            } => {
+                let escalator_xform = rotated(position, rotation);
+                let escalator = spawn_escalator(
+                    commands,
+                    materials.escalator.clone_weak(),
+                    escalator_xform,
+                    Escalator {
+                        length,
+                        facing,
+                        travel,
+                        speed,
+                        state,
+                    },
+                );
+                register_name(&mut names, name, escalator);
+                spawned.push(escalator);
+
+                for (step_transform, track_position, track_length) in
+                    steps(escalator_xform, length, step_size, facing)
+                {
+                    let step = spawn_step(
+                        commands,
+                        materials.step.clone_weak(),
+                        escalator,
+                        step_transform,
+                        step_size,
+                        track_position,
+                        track_length,
+                    );
+                    with_material(commands, step, step_material);
+                    spawned.push(step);
+                }
+            }
+            LevelEntity::Ladder { position, size } => {
+                spawned.push(spawn_ladder(
+                    commands,
+                    materials.ladder.clone_weak(),
+                    t(position),
+                    size,
+                ));
+            }
+            LevelEntity::Crate {
+                position,
+                size,
+                rotation,
+                ref collides_with,
+                mass,
+                material,
+            } => {
+                let crate_ = spawn_crate(
+                    commands,
+                    materials.crate_.clone_weak(),
+                    size,
+                    rotated(position, rotation),
+                );
+                collide_with(commands, crate_, CollisionLayers::CRATE, collides_with);
+                if let Some(mass) = mass {
+                    commands.entity(crate_).insert(Mass(mass));
+                }
+                with_material(commands, crate_, material);
+                spawned.push(crate_);
+            }
+            LevelEntity::Player {
+                position,
+                size,
+                material,
+            } => {
+                let player =
+                    spawn_player(commands, materials.player.clone_weak(), size, t(position));
+                with_material(commands, player, material);
+                spawned.push(player);
+            }
+            LevelEntity::PressurePlate {
+                position,
+                size,
+                ref name,
+            } => {
+                let plate = spawn_pressure_plate(
+                    commands,
+                    materials.pressure_plate.clone_weak(),
+                    size,
+                    t(position),
+                );
+                register_name(&mut names, name, plate);
+                spawned.push(plate);
+            }
+            LevelEntity::Lever {
+                position,
+                size,
+                ref name,
+            } => {
+                let lever = spawn_lever(commands, materials.lever.clone_weak(), size, t(position));
+                register_name(&mut names, name, lever);
+                spawned.push(lever);
+            }
+            LevelEntity::Door {
+                position,
+                size,
+                ref name,
+            } => {
+                let door = spawn_door(commands, materials.door.clone_weak(), size, t(position));
+                register_name(&mut names, name, door);
+                spawned.push(door);
+            }
+            LevelEntity::Exit { position, size } => {
+                spawned.push(spawn_exit(
+                    commands,
+                    materials.exit.clone_weak(),
+                    size,
+                    t(position),
+                ));
+            }
+            LevelEntity::TargetPad { position, size } => {
+                spawned.push(spawn_target_pad(
+                    commands,
+                    materials.target_pad.clone_weak(),
+                    size,
+                    t(position),
+                ));
+            }
+        }
+    }
+
+    for (index, entity) in spawned.into_iter().enumerate() {
+        commands.entity(entity).insert(InLevel(index));
+    }
+
+    let lookup = |name: &String| *names.get(name).expect("wires checked above");
+
+    let mut sources: HashMap<Entity, Vec<Entity>> = HashMap::new();
+    for wire in level.wires.iter() {
+        sources
+            .entry(lookup(&wire.to))
+            .or_default()
+            .push(lookup(&wire.from));
+    }
+
+    for (target, sources) in sources {
+        commands.entity(target).insert(Powered { sources });
+    }
+
+    Ok(())
+}
@@ -0,0 +1,414 @@
+use bevy::{app::Events, core::FixedTimestep, prelude::*};
+use serde::{Deserialize, Serialize};
+
+mod broadphase;
+mod climb;
+mod contact;
+mod grab;
+mod gravity;
+mod layers;
+mod level;
+mod material;
+mod path;
+mod physics;
+mod progression;
+mod replay;
+mod rewind;
+mod rotation;
+mod signal;
+mod simulation;
+mod solver;
+mod spawn;
+mod support;
+
+pub use broadphase::*;
+pub use climb::*;
+pub use contact::*;
+pub use grab::*;
+pub use gravity::*;
+pub use layers::*;
+pub use level::*;
+pub use material::*;
+pub use path::*;
+pub use physics::*;
+pub use progression::*;
+pub use replay::*;
+pub use rewind::*;
+pub use rotation::*;
+pub use signal::*;
+pub use simulation::*;
+pub use solver::*;
+pub use spawn::*;
+pub use support::*;
+
+pub const BASE_SPEED_FACTOR: f32 = 70.0;
+
+/// Length of one physics tick, in seconds.
+pub const TIMESTEP: f64 = 1.0 / 60.0;
+
+/// Distance covered in one tick by a body with unit velocity.
+pub const DELTA: f32 = BASE_SPEED_FACTOR * TIMESTEP as f32;
+
+#[derive(Debug, Hash, PartialEq, Eq, Clone, StageLabel)]
+pub struct PhysicsStage;
+
+#[derive(Debug, Hash, PartialEq, Eq, Clone, SystemLabel)]
+pub struct ContactLabel;
+
+#[derive(Debug, Hash, PartialEq, Eq, Clone, SystemLabel)]
+pub struct SwitchLabel;
+
+#[derive(Debug, Hash, PartialEq, Eq, Clone, SystemLabel)]
+pub struct SignalLabel;
+
+#[derive(Debug, Hash, PartialEq, Eq, Clone, SystemLabel)]
+pub struct PrePhysicsLabel;
+
+/*
+Labels for single systems, to chain systems in the same set that write the
+same component. Bevy can't tell whether the results depend on the order, and
+would be free to run them in a different order every time the stage is built.
+*/
+#[derive(Debug, Hash, PartialEq, Eq, Clone, SystemLabel)]
+pub struct PressurePlateLabel;
+
+#[derive(Debug, Hash, PartialEq, Eq, Clone, SystemLabel)]
+pub struct TargetPadLabel;
+
+#[derive(Debug, Hash, PartialEq, Eq, Clone, SystemLabel)]
+pub struct StepVelocityLabel;
+
+#[derive(Debug, Hash, PartialEq, Eq, Clone, SystemLabel)]
+pub struct PathVelocityLabel;
+
+#[derive(Debug, Hash, PartialEq, Eq, Clone, SystemLabel)]
+pub struct PlayerVelocityLabel;
+
+/// Within `ForceLabel`, so forces that replace momentum outright come after
+/// the surfaces have had their say.
+#[derive(Debug, Hash, PartialEq, Eq, Clone, SystemLabel)]
+pub struct NormalForceLabel;
+
+#[derive(Debug, Hash, PartialEq, Eq, Clone, SystemLabel)]
+pub struct JumpLabel;
+
+#[derive(Debug, Hash, PartialEq, Eq, Clone, SystemLabel)]
+pub struct ClimbLabel;
+
+#[derive(Debug, Hash, PartialEq, Eq, Clone, SystemLabel)]
+pub struct ForceLabel;
+
+#[derive(Debug, Hash, PartialEq, Eq, Clone, SystemLabel)]
+pub struct IndependentVelocityLabel;
+
+#[derive(Debug, Hash, PartialEq, Eq, Clone, SystemLabel)]
+pub struct DependentVelocityLabel;
+
+#[derive(Debug, Hash, PartialEq, Eq, Clone, SystemLabel)]
+pub struct PositionLabel;
+
+#[derive(Debug, Hash, PartialEq, Eq, Clone, SystemLabel)]
+pub struct PreCollisionLabel;
+
+#[derive(Debug, Hash, PartialEq, Eq, Clone, SystemLabel)]
+pub struct CollisionLabel;
+
+pub struct Escalator {
+    pub length: f32,
+    pub facing: Facing,
+    pub travel: Travel,
+    /// Multiplier on `BASE_SPEED_FACTOR` for how fast the steps move.
+    pub speed: f32,
+    pub state: EscalatorState,
+}
+
+/// Which side of an escalator is its top.
+///
+/// The geometry in `steps`, `step_velocity` and `spawn_escalator` is written
+/// for an escalator rising to the left; `Right` mirrors it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Facing {
+    Left,
+    Right,
+}
+
+impl Default for Facing {
+    fn default() -> Self {
+        Facing::Left
+    }
+}
+
+impl Facing {
+    /// Maps an offset from a left-facing escalator's center onto this facing.
+    pub fn orient(self, offset: Vec2) -> Vec2 {
+        match self {
+            Facing::Left => offset,
+            Facing::Right => Vec2::new(-offset.x, offset.y),
+        }
+    }
+}
+
+/// Whether riders on top of an escalator are carried toward its top or its bottom.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Travel {
+    Up,
+    Down,
+}
+
+impl Default for Travel {
+    fn default() -> Self {
+        Travel::Up
+    }
+}
+
+impl Travel {
+    /// Direction in which step tracks advance.
+    pub fn sign(self) -> f32 {
+        match self {
+            Travel::Up => 1.0,
+            Travel::Down => -1.0,
+        }
+    }
+}
+
+/// Whether an escalator's steps are moving, and which way relative to its `Travel`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EscalatorState {
+    Running,
+    Stopped,
+    Reversing,
+}
+
+impl Default for EscalatorState {
+    fn default() -> Self {
+        EscalatorState::Running
+    }
+}
+
+impl EscalatorState {
+    pub fn sign(self) -> f32 {
+        match self {
+            EscalatorState::Running => 1.0,
+            EscalatorState::Stopped => 0.0,
+            EscalatorState::Reversing => -1.0,
+        }
+    }
+}
+
+impl Escalator {
+    /// Signed distance each of this escalator's step tracks advances per tick.
+    pub fn track_delta(&self) -> f32 {
+        self.travel.sign() * self.state.sign() * self.speed * DELTA
+    }
+}
+
+pub struct Step {
+    pub escalator: Entity,
+    pub length: f32,
+}
+
+#[derive(Debug)]
+pub struct Track {
+    pub position: f32,
+    pub length: f32,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Velocity(pub Vec2);
+
+/// How hard a body is to shove in a collision.
+///
+/// Bodies without a `Mass`, or without a `Velocity`, are immovable: ground is
+/// static, and steps follow their tracks whatever pushes on them.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Mass(pub f32);
+
+impl Mass {
+    pub fn inverse(self) -> f32 {
+        if self.0.is_finite() {
+            1.0 / self.0
+        } else {
+            0.0
+        }
+    }
+}
+
+impl Default for Mass {
+    fn default() -> Self {
+        Mass(1.0)
+    }
+}
+
+pub struct Ground;
+
+#[derive(PartialEq, Eq, Hash)]
+pub struct Crate;
+
+pub struct Player;
+
+pub struct Ladder;
+
+/// Marks an entity spawned by `spawn_level`, to be despawned when the level ends.
+///
+/// Numbers entities in the order they were spawned, which unlike `Entity`
+/// doesn't depend on what was spawned and despawned before the level.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct InLevel(pub usize);
+
+/// Registers the escalator physics pipeline on a fixed-timestep stage that
+/// only runs in `AppState::Playing`.
+pub struct StaircasesPhysicsPlugin;
+
+impl Plugin for StaircasesPhysicsPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        // physics runs on a fixed tick so that a given input sequence
+        // always produces the same trajectories, regardless of frame rate
+        let mut stage = SystemStage::parallel().with_run_criteria(FixedTimestep::step(TIMESTEP));
+        add_physics_systems(&mut stage);
+
+        app.add_state(AppState::Menu)
+            .init_resource::<Broadphase>()
+            .init_resource::<Contacts>()
+            .init_resource::<Events<CollisionEvent>>()
+            .init_resource::<GoalReached>()
+            .init_resource::<History>()
+            .init_resource::<ReplayMode>()
+            .init_resource::<Solver>()
+            .add_stage_after(CoreStage::Update, PhysicsStage, PlayingStage(stage));
+    }
+}
+
+/// Runs the wrapped stage only while `State<AppState>` is `Playing`.
+///
+/// Gating the whole stage, rather than its systems, keeps the fixed timestep
+/// from banking up time while paused and replaying it all on resume.
+struct PlayingStage(SystemStage);
+
+impl Stage for PlayingStage {
+    fn run(&mut self, world: &mut World) {
+        let playing = world
+            .get_resource::<State<AppState>>()
+            .map_or(false, |state| *state.current() == AppState::Playing);
+
+        if playing {
+            self.0.run(world);
+        }
+    }
+}
+
+/// Adds one tick's worth of physics systems to `stage`, in pipeline order.
+pub fn add_physics_systems(stage: &mut SystemStage) {
+    stage
+        // record or play back this tick's keys before anything reads them
+        .add_system(replay_input.exclusive_system().at_start())
+        // events last two ticks, so every reader in the stage sees each one
+        .add_system(
+            Events::<CollisionEvent>::update_system
+                .system()
+                .before(ContactLabel),
+        )
+        .add_system(update_contacts.system().label(ContactLabel))
+        // read switches from last tick's positions, then drive what they're wired to
+        .add_system_set(
+            SystemSet::new()
+                .label(SwitchLabel)
+                .after(ContactLabel)
+                .with_system(pressure_plates.system().label(PressurePlateLabel))
+                .with_system(
+                    target_pads
+                        .system()
+                        .label(TargetPadLabel)
+                        .after(PressurePlateLabel),
+                )
+                .with_system(levers.system().after(TargetPadLabel)),
+        )
+        .add_system_set(
+            SystemSet::new()
+                .label(SignalLabel)
+                .after(SwitchLabel)
+                .with_system(power_escalators.system())
+                .with_system(power_conveyors.system())
+                .with_system(power_doors.system())
+                .with_system(check_goal.system()),
+        )
+        .add_system(reset_velocity.system().label(PrePhysicsLabel))
+        .add_system(
+            update_supports
+                .system()
+                .label(PrePhysicsLabel)
+                .after(ContactLabel),
+        )
+        // systems that don't edit velocity
+        .add_system(
+            update_step_track
+                .system()
+                .label(PrePhysicsLabel)
+                .after(SignalLabel),
+        )
+        .add_system(update_path_progress.system().label(PrePhysicsLabel))
+        // after contacts, which judge one-way landings by last tick's momentum
+        .add_system(gravity.system().label(PrePhysicsLabel).after(ContactLabel))
+        // contacts, jumps, ladders, grabs and tipping act on what gravity left
+        .add_system_set(
+            SystemSet::new()
+                .label(ForceLabel)
+                .after(PrePhysicsLabel)
+                .after(ContactLabel)
+                .with_system(normal_force.system().label(NormalForceLabel))
+                .with_system(jump.system().label(JumpLabel).after(NormalForceLabel))
+                // a ladder overrides a jump, and grabbing one lets go of any crate
+                .with_system(climb.system().label(ClimbLabel).after(JumpLabel))
+                .with_system(grab.system().after(ClimbLabel))
+                .with_system(tumble.system()),
+        )
+        // first pass at setting velocities
+        .add_system_set(
+            SystemSet::new()
+                .label(IndependentVelocityLabel)
+                .after(ForceLabel)
+                .with_system(step_velocity.system().label(StepVelocityLabel))
+                .with_system(
+                    path_velocity
+                        .system()
+                        .label(PathVelocityLabel)
+                        .after(StepVelocityLabel),
+                )
+                .with_system(
+                    player_velocity
+                        .system()
+                        .label(PlayerVelocityLabel)
+                        .after(PathVelocityLabel),
+                )
+                .with_system(apply_momentum.system().after(PlayerVelocityLabel)),
+        )
+        // held crates follow the player
+        .add_system(
+            drag.system()
+                .after(IndependentVelocityLabel)
+                .before(DependentVelocityLabel),
+        )
+        // riders inherit the velocity of whatever they stand on
+        .add_system(
+            carry
+                .system()
+                .label(DependentVelocityLabel)
+                .after(IndependentVelocityLabel),
+        )
+        // integrate
+        .add_system(
+            update_position
+                .system()
+                .label(PositionLabel)
+                .after(DependentVelocityLabel),
+        )
+        .add_system(update_rotation.system().after(PositionLabel))
+        // push overlapping bodies apart, then record the finished tick or
+        // replace it with an earlier one
+        .add_system(
+            solve_collisions
+                .exclusive_system()
+                .at_end()
+                .label(CollisionLabel),
+        )
+        .add_system(rewind.exclusive_system().at_end().after(CollisionLabel));
+}
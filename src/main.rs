@@ -1,29 +1,34 @@
 use bevy::{diagnostic::Diagnostics, prelude::*};
 use bevy_prototype_debug_lines::{DebugLines, DebugLinesPlugin};
-use nalgebra::{Isometry2, Point2, Vector2};
-use parry2d::{query, shape::ConvexPolygon};
-
-const BASE_SPEED_FACTOR: f32 = 70.0;
-
-#[derive(Debug, Hash, PartialEq, Eq, Clone, SystemLabel)]
-pub struct PrePhysicsLabel;
-#[derive(Debug, Hash, PartialEq, Eq, Clone, SystemLabel)]
-pub struct IndependentVelocityLabel;
-
-#[derive(Debug, Hash, PartialEq, Eq, Clone, SystemLabel)]
-pub struct DependentVelocityLabel;
-
-#[derive(Debug, Hash, PartialEq, Eq, Clone, SystemLabel)]
-pub struct PositionLabel;
-
-#[derive(Debug, Hash, PartialEq, Eq, Clone, SystemLabel)]
-pub struct PreCollisionLabel;
-
-#[derive(Debug, Hash, PartialEq, Eq, Clone, SystemLabel)]
-pub struct CollisionLabel;
+use parry2d::shape::ConvexPolygon;
+use staircases::*;
 
 fn main() {
+    // `--record <file>` saves the current level's inputs there on SAVE_REPLAY_KEY;
+    // `--replay <file>` plays a saved run back instead of reading the keyboard
+    let mut record = None;
+    let mut replay = ReplayMode::Off;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--record" => record = Some(args.next().expect("--record <file>")),
+            "--replay" => {
+                let path = args.next().expect("--replay <file>");
+                replay = ReplayMode::Play {
+                    replay: Replay::from_ron(
+                        &std::fs::read_to_string(path).expect("replay file"),
+                    )
+                    .expect("replay"),
+                    tick: 0,
+                };
+            }
+            _ => panic!("unknown argument `{}`", arg),
+        }
+    }
+
     App::build()
+    .insert_resource(RecordPath(record))
+    .insert_resource(replay)
     .insert_resource(WindowDescriptor {
         vsync: false,
         ..Default::default()
@@ -37,195 +42,37 @@ fn main() {
         .add_system(bevy::input::system::exit_on_esc_system.system())
         .add_system(framerate.system())
 
-
-        .add_system(reset_velocity.system().label(PrePhysicsLabel))
-        // systems that don't edit velocity
-        .add_system(update_step_track.system().label(PrePhysicsLabel))
-        // first pass at setting velocities
+        .add_plugin(StaircasesPhysicsPlugin)
+        .add_system_set(SystemSet::on_update(AppState::Menu).with_system(start_game.system()))
+        .add_system_set(SystemSet::on_enter(AppState::Menu).with_system(unload_level.system()))
         .add_system_set(
-            SystemSet::new()
-                .label(IndependentVelocityLabel)
-                .after(PrePhysicsLabel)
-                .with_system(step_velocity.system())
-                .with_system(player_velocity.system())
-                .with_system(falling_velocity.system())
-                .with_system(normal_force.system())
-                .with_system(ladder.system()),
+            SystemSet::on_enter(AppState::Playing)
+                .with_system(load_level.system())
+                .with_system(start_run.system()),
         )
-
-        .add_system(friction.system().label(DependentVelocityLabel).after(IndependentVelocityLabel))
-
-        // integrate
-        .add_system(
-            update_position
-                .system()
-                .label(PositionLabel)
-                .after(DependentVelocityLabel),
-        )
-        // second pass at setting velocities; impulses to avoid collisions
-        .add_system(
-            reset_velocity
-                .system()
-                .after(PositionLabel)
-                .label(PreCollisionLabel),
+        .add_system_set(
+            SystemSet::on_update(AppState::Playing)
+                .with_system(complete_level.system())
+                .with_system(save_replay.system()),
         )
-        .add_system(
-            process_collisions
-                .system()
-                .after(PreCollisionLabel)
-                .label(CollisionLabel),
+        .add_system_set(
+            SystemSet::on_update(AppState::LevelComplete).with_system(next_level.system()),
         )
-        .add_system(update_position.system().after(CollisionLabel))
-        
-        // .add_system(process_collisions.system())
-        // .add_system(update_position.system())
-        // .add_system(reset_velocity.system())
-        // .add_system(process_collisions.system())
-        // .add_system(update_position.system())
-        // .add_system(reset_velocity.system())
-        // .add_system(process_collisions.system())
-        // .add_system(update_position.system())
         .add_system(lines.system())
         .run();
 }
 
-fn falling_velocity(mut q: Query<&mut Velocity>) {
-    for mut velocity in q.iter_mut() {
-        velocity.0.y -= 1.0;
-    }
-}
-
-fn normal_force(
-    q: Query<(Entity, &Transform, &ConvexPolygon), Without<Ladder>>,
-
-    mut velocities: Query<&mut Velocity>,
-
-    steps: Query<&Step>,
-) {
-    for (entity_a, xform_a, poly_a) in q.iter() {
-        for (entity_b, xform_b, poly_b) in q.iter() {
-            if entity_a >= entity_b {
-                continue;
-            }
-
-            if let Ok(step_a) = steps.get(entity_a) {
-                if step_a.escalator == entity_b {
-                    continue;
-                }
-
-                if let Ok(_step_b) = steps.get(entity_b) {
-                    continue;
-                }
-            }
-
-            if let Ok(step) = steps.get(entity_b) {
-                if step.escalator == entity_a {
-                    continue;
-                }
-            }
-
-            if let Some(contact) = collision(poly_a, &xform_a, poly_b, &xform_b) {
-                // HACK: collisions shouldn't push down(?)
-
-                if contact.normal1.y < 0. {
-                    // apply normal force to a
-
-                    if let Ok(mut velocity_a) = velocities.get_mut(entity_a) {
-                        velocity_a.0.y += 1.0;
-                    }
-                }
-
-                if contact.normal2.y < 0.0 {
-                    // apply normal force to b
-
-                    if let Ok(mut velocity_b) = velocities.get_mut(entity_b) {
-                        velocity_b.0.y += 1.0;
-                    }
-                }
-            }
-        }
-    }
-}
-
-/*
-Friction is applied between two bodies in contact.
-It is perpendicular to the normal of the comment
-and resists motion of the top entity relative to the bottom entity.
-*/
-fn friction(
-    q: Query<(Entity, &Transform, &ConvexPolygon), Without<Ladder>>,
+/// Levels in the order they are played.
+const LEVELS: &[&str] = &["assets/levels/01.ron", "assets/levels/02.ron"];
 
-    mut velocities: Query<&mut Velocity>,
+/// Key that starts the game from the menu and continues past a beaten level.
+const CONTINUE_KEY: KeyCode = KeyCode::Return;
 
-    steps: Query<&Step>,
-) {
-
-    dbg!("friction");
+/// Key that writes the current level's recording to the `--record` file.
+const SAVE_REPLAY_KEY: KeyCode = KeyCode::F5;
 
-    for (entity_a, xform_a, poly_a) in q.iter() {
-        for (entity_b, xform_b, poly_b) in q.iter() {
-            if entity_a >= entity_b {
-                continue;
-            }
-
-            if let Ok(step_a) = steps.get(entity_a) {
-                if step_a.escalator == entity_b {
-                    continue;
-                }
-
-                if let Ok(_step_b) = steps.get(entity_b) {
-                    continue;
-                }
-            }
-
-            if let Ok(step) = steps.get(entity_b) {
-                if step.escalator == entity_a {
-                    continue;
-                }
-            }
-
-            if let Some(contact) = collision(poly_a, &xform_a, poly_b, &xform_b) {
-                // friction should be
-                // proportional to velocity
-                // orthogonal to normal
-
-                // friction from b to a:
-
-                let FRICTION_COEFFICIENT: f32 = 1.0;
-
-                if contact.normal2.y > 0. {
-                    if let Ok(velocity_b) = velocities.get_mut(entity_b) {
-                        let velocity_b = velocity_b.clone();
-
-                        if let Ok(mut velocity_a) = velocities.get_mut(entity_a) {
-                            let friction = FRICTION_COEFFICIENT
-                                * velocity_b.0
-                                * contact.normal1.perp().normalize();
-
-                            // project b's velocity onto
-                            velocity_a.0 += friction;
-                        }
-                    }
-                }
-
-                if contact.normal1.y > 0. {
-                    if let Ok(velocity_a) = velocities.get_mut(entity_a) {
-                        let velocity_a = velocity_a.clone();
-
-                        if let Ok(mut velocity_b) = velocities.get_mut(entity_b) {
-                            // project b's velocity onto
-                            let friction = FRICTION_COEFFICIENT
-                                * velocity_a.0
-                                * contact.normal2.perp().normalize();
-
-                            velocity_b.0 += friction;
-                        }
-                    }
-                }
-            }
-        }
-    }
-}
+/// Where to save recordings, if `--record` was given.
+struct RecordPath(Option<String>);
 
 #[allow(dead_code)]
 fn framerate(diagnostics: Res<Diagnostics>) {
@@ -234,37 +81,6 @@ fn framerate(diagnostics: Res<Diagnostics>) {
     }
 }
 
-struct Escalator {
-    length: f32,
-}
-
-struct Step {
-    escalator: Entity,
-    length: f32,
-}
-
-#[derive(Debug)]
-struct Track {
-    position: f32,
-    length: f32,
-}
-
-#[derive(Clone, PartialEq, Debug)]
-struct Velocity(Vec2);
-
-struct Ground;
-
-#[derive(PartialEq, Eq, Hash)]
-struct Crate;
-
-struct Player;
-
-struct Ladder;
-
-fn t(x: f32, y: f32) -> Transform {
-    Transform::from_translation(Vec3::new(x, y, 0.0))
-}
-
 #[allow(unused_variables)]
 fn setup(
     mut commands: Commands,
@@ -273,6 +89,9 @@ fn setup(
     mut texture_atlases: ResMut<Assets<TextureAtlas>>,
 
     mut materials: ResMut<Assets<ColorMaterial>>,
+
+    replay: Res<ReplayMode>,
+    mut state: ResMut<State<AppState>>,
 ) {
     commands.spawn_bundle(OrthographicCameraBundle::new_2d());
     commands.spawn_bundle(UiCameraBundle::default());
@@ -287,605 +106,137 @@ fn setup(
     let ground_handle =
         materials.add(Color::rgb(87.0 / 255.0, 114.0 / 255.0, 119.0 / 255.0).into());
     let step_handle = materials.add(Color::rgb(168.0 / 255.0, 202.0 / 255.0, 88.0 / 255.0).into());
-
-    {
-        spawn_ground(
-            &mut commands,
-            ground_handle.clone_weak(),
-            Vec2::new(250., 50.),
-            t(-100., 0.),
-        );
-
-        let escalator_xform = t(-125., 50.);
-        let escalator_length = 200.0;
-        let escalator = spawn_escalator(
-            &mut commands,
-            escalator_handle.clone_weak(),
-            escalator_xform,
-            escalator_length,
-        );
-
-        for (step_transform, track_position, track_length) in
-            steps(escalator_xform, escalator_length, 50.)
-        {
-            spawn_step(
-                &mut commands,
-                step_handle.clone_weak(),
-                escalator,
-                step_transform,
-                50.0,
-                track_position,
-                track_length,
-            );
-        }
-
-        spawn_ground(
-            &mut commands,
-            ground_handle.clone_weak(),
-            Vec2::new(200., 50.),
-            t(125., 50.),
-        );
-
-        spawn_ladder(
-            &mut commands,
-            crate_handle.clone_weak(),
-            t(250.0, 0.0),
-            Vec2::new(50.0, 300.0),
-        );
-
-        spawn_crate(
-            &mut commands,
-            player_handle.clone_weak(),
-            Vec2::new(50., 50.),
-            t(100., 100.),
-        );
-
-        spawn_player(
-            &mut commands,
-            player_handle.clone_weak(),
-            Vec2::new(50., 100.),
-            t(150., 100.),
-        );
-    }
-    spawn_crate(
-        &mut commands,
-        crate_handle.clone_weak(),
-        Vec2::new(50., 50.),
-        t(200., 100.),
-    );
-
-    // lower bit
-    spawn_ground(
-        &mut commands,
-        ground_handle.clone_weak(),
-        Vec2::new(700., 50.),
-        t(0., -250.),
-    );
-
-    spawn_ground(
-        &mut commands,
-        ground_handle.clone_weak(),
-        Vec2::new(100., 50.),
-        t(-460., -250.),
-    );
-
-    spawn_ground(
-        &mut commands,
-        ground_handle.clone_weak(),
-        Vec2::new(100., 50.),
-        t(-360., -300.),
-    );
-
-    // cheese prevention
-    spawn_ground(
-        &mut commands,
-        ground_handle.clone_weak(),
-        Vec2::new(50., 50.),
-        t(320., -200.),
-    );
-}
-
-#[allow(dead_code)]
-fn spawn_escalator(
-    commands: &mut Commands,
-    texture: Handle<TextureAtlas>,
-    transform: Transform,
-    length: f32,
-) -> Entity {
-    commands
-        .spawn()
-        .insert_bundle(SpriteSheetBundle {
-            sprite: TextureAtlasSprite {
-                color: Color::rgba(1.0, 1.0, 1.0, 0.5),
-                ..TextureAtlasSprite::default()
-            },
-            visible: Visible {
-                is_visible: true,
-                is_transparent: true,
-            },
-            texture_atlas: texture.clone_weak(),
-            transform,
-            ..Default::default()
+    let switch_handle =
+        materials.add(Color::rgb(222.0 / 255.0, 184.0 / 255.0, 65.0 / 255.0).into());
+    let door_handle = materials.add(Color::rgb(60.0 / 255.0, 60.0 / 255.0, 72.0 / 255.0).into());
+    let exit_handle =
+        materials.add(Color::rgb(240.0 / 255.0, 240.0 / 255.0, 240.0 / 255.0).into());
+    let target_pad_handle =
+        materials.add(Color::rgb(211.0 / 255.0, 115.0 / 255.0, 190.0 / 255.0).into());
+
+    let levels = LEVELS
+        .iter()
+        .map(|path| {
+            Level::from_ron(&std::fs::read_to_string(path).expect("level file")).expect("level")
         })
-        .insert(Escalator { length })
-        .insert(Velocity(Vec2::ZERO))
-        .insert(
-            ConvexPolygon::from_convex_hull(&[
-                Point2::new(-length / 2.0, length / 2.0 - 10.0),
-                Point2::new(length / 2.0, -length / 2.0),
-                Point2::new(-length / 2.0, -length / 2.0),
-            ])
-            .expect("polygon"),
-        )
-        .id()
-}
+        .collect();
+    let mut campaign = Campaign::new(levels);
 
-#[allow(dead_code)]
-fn spawn_ladder(
-    commands: &mut Commands,
-    material: Handle<ColorMaterial>,
-    transform: Transform,
-    size: Vec2,
-) {
-    commands
-        .spawn_bundle(SpriteBundle {
-            material,
-            transform,
-            sprite: Sprite::new(size),
-            ..Default::default()
-        })
-        .insert(Ladder)
-        .insert(
-            ConvexPolygon::from_convex_hull(&[
-                Point2::new(-size.x / 2.0, size.y / 2.0),
-                Point2::new(size.x / 2.0, size.y / 2.0),
-                Point2::new(size.x / 2.0, -size.y / 2.0),
-                Point2::new(-size.x / 2.0, -size.y / 2.0),
-            ])
-            .expect("poly"),
-        );
-}
-
-#[allow(dead_code)]
-fn spawn_step(
-    commands: &mut Commands,
-    material: Handle<ColorMaterial>,
-    escalator: Entity,
-    transform: Transform,
-    length: f32,
-    track_position: f32,
-    track_length: f32,
-) -> Entity {
-    commands
-        .spawn()
-        .insert_bundle(SpriteBundle {
-            material,
-            transform,
-            sprite: Sprite::new(Vec2::splat(length)),
-            ..Default::default()
-        })
-        .insert(Step { escalator, length })
-        .insert(Velocity(Vec2::ZERO))
-        .insert(Track {
-            length: track_length,
-            position: track_position,
-        })
-        .insert(
-            ConvexPolygon::from_convex_hull(&[
-                Point2::new(-length / 2.0, length / 2.0),
-                Point2::new(length / 2.0, length / 2.0),
-                Point2::new(length / 2.0, -length / 2.0),
-                Point2::new(-length / 2.0, -length / 2.0),
-            ])
-            .expect("poly"),
-        )
-        .id()
-}
-
-fn spawn_ground(
-    commands: &mut Commands,
-    material: Handle<ColorMaterial>,
-    ground_box: Vec2,
-    transform: Transform,
-) {
-    commands
-        .spawn()
-        .insert_bundle(SpriteBundle {
-            sprite: Sprite::new(ground_box),
-            material,
-            transform,
-            ..Default::default()
-        })
-        .insert(Ground)
-        .insert(
-            ConvexPolygon::from_convex_hull(&[
-                Point2::new(-ground_box.x / 2.0, ground_box.y / 2.0),
-                Point2::new(ground_box.x / 2.0, ground_box.y / 2.0),
-                Point2::new(ground_box.x / 2.0, -ground_box.y / 2.0),
-                Point2::new(-ground_box.x / 2.0, -ground_box.y / 2.0),
-            ])
-            .expect("polygon"),
-        );
-}
+    // a replay skips the menu and starts on the level it was recorded on
+    if let ReplayMode::Play { replay, .. } = &*replay {
+        campaign.current = LEVELS
+            .iter()
+            .position(|path| *path == replay.level)
+            .expect("replay level is in LEVELS");
+        state.set(AppState::Playing).expect("enter Playing");
+    }
 
-#[allow(dead_code)]
-fn spawn_player(
-    commands: &mut Commands,
-    material: Handle<ColorMaterial>,
-    size: Vec2,
-    transform: Transform,
-) {
-    commands
-        .spawn()
-        .insert_bundle(SpriteBundle {
-            sprite: Sprite::new(size),
-            transform,
-            material,
-            ..SpriteBundle::default()
-        })
-        .insert(Player)
-        .insert(Velocity(Vec2::ZERO))
-        .insert(
-            ConvexPolygon::from_convex_hull(&[
-                Point2::new(-size.x / 2.0, size.y / 2.0),
-                Point2::new(size.x / 2.0, size.y / 2.0),
-                Point2::new(size.x / 2.0, -size.y / 2.0),
-                Point2::new(-size.x / 2.0, -size.y / 2.0),
-            ])
-            .expect("poly"),
-        );
-}
+    commands.insert_resource(campaign);
 
-#[allow(dead_code)]
-fn spawn_crate(
-    commands: &mut Commands,
-    material: Handle<ColorMaterial>,
-    size: Vec2,
-    transform: Transform,
-) -> Entity {
-    commands
-        .spawn_bundle(SpriteBundle {
-            transform,
-            sprite: Sprite::new(size),
-            material,
-            ..Default::default()
-        })
-        .insert(Crate {})
-        .insert(Velocity(Vec2::ZERO))
-        .insert(
-            ConvexPolygon::from_convex_hull(&[
-                Point2::new(-size.x / 2.0, size.y / 2.0),
-                Point2::new(size.x / 2.0, size.y / 2.0),
-                Point2::new(size.x / 2.0, -size.y / 2.0),
-                Point2::new(-size.x / 2.0, -size.y / 2.0),
-            ])
-            .expect("poly"),
-        )
-        .id()
+    commands.insert_resource(LevelMaterials {
+        escalator: escalator_handle,
+        step: step_handle,
+        ground: ground_handle,
+        ladder: crate_handle.clone(),
+        crate_: crate_handle,
+        player: player_handle,
+        pressure_plate: switch_handle.clone(),
+        lever: switch_handle,
+        door: door_handle,
+        exit: exit_handle,
+        target_pad: target_pad_handle,
+    });
 }
 
-#[allow(dead_code)]
-fn steps(
-    escalator_transform: Transform,
-    escalator_length: f32,
-    step_length: f32,
-) -> Vec<(Transform, f32, f32)> {
-    let mut result = vec![];
-    let n = (escalator_length / step_length) as i32;
-
-    let track_length = (2.0 * (n as f32 - 1.0) + 2.0) * step_length;
-    let mut track_position = 0.0;
-
-    // A
-    result.push((
-        Transform::from_translation(Vec3::new(
-            escalator_transform.translation.x - escalator_length / 2.0 + step_length / 2.0,
-            escalator_transform.translation.y + escalator_length / 2.0 - step_length / 2.0,
-            0.0,
-        )),
-        track_position,
-        track_length,
-    ));
-
-    track_position += step_length;
-
-    // B
-
-    for index in 0..n - 2 {
-        result.push((
-            Transform::from_translation(Vec3::new(
-                escalator_transform.translation.x - escalator_length / 2.0
-                    + step_length / 2.0
-                    + index as f32 * step_length,
-                escalator_transform.translation.y + escalator_length / 2.0
-                    - 3.0 * step_length / 2.0
-                    - index as f32 * step_length,
-                0.0,
-            )),
-            track_position,
-            track_length,
-        ));
-        track_position += step_length;
+fn start_game(keys: Res<Input<KeyCode>>, mut state: ResMut<State<AppState>>) {
+    if keys.just_pressed(CONTINUE_KEY) {
+        state.set(AppState::Playing).expect("enter Playing");
     }
+}
 
-    // C
-    result.push((
-        Transform::from_translation(Vec3::new(
-            escalator_transform.translation.x + escalator_length / 2.0 - 3.0 * step_length / 2.0,
-            escalator_transform.translation.y - escalator_length / 2.0 + step_length / 2.0,
-            0.0,
-        )),
-        track_position,
-        track_length,
-    ));
-    track_position += step_length;
-
-    // D
-    for index in 0..n {
-        result.push((
-            Transform::from_translation(Vec3::new(
-                escalator_transform.translation.x + escalator_length / 2.0
-                    - step_length / 2.0
-                    - (index as f32) * step_length,
-                escalator_transform.translation.y
-                    + -escalator_length / 2.0
-                    + step_length / 2.0
-                    + (index as f32) * step_length,
-                0.0,
-            )),
-            track_position,
-            track_length,
-        ));
-        track_position += step_length;
+fn unload_level(mut commands: Commands, level: Query<Entity, With<InLevel>>) {
+    for entity in level.iter() {
+        commands.entity(entity).despawn();
     }
-    result
 }
 
-fn player_velocity(
-    keyboard_input: Res<Input<KeyCode>>,
-    mut query: Query<(&Player, &mut Velocity)>,
+fn load_level(
+    mut commands: Commands,
+    level: Query<Entity, With<InLevel>>,
+    campaign: Res<Campaign>,
+    materials: Res<LevelMaterials>,
+    mut state: ResMut<State<AppState>>,
 ) {
-    dbg!("player_velocity");
-    for (_player, mut velocity) in query.iter_mut() {
-
-        let mut x_velocity = 0.0;
-        if keyboard_input.pressed(KeyCode::A) {
-            x_velocity += -1.0;
-        }
-        if keyboard_input.pressed(KeyCode::D) {
-            x_velocity += 1.0;
-        }
+    for entity in level.iter() {
+        commands.entity(entity).despawn();
+    }
 
-        *velocity = Velocity(Vec2::new(x_velocity, velocity.0.y));
-        // dbg!(velocity.0);
+    if let Err(error) = spawn_level(&mut commands, campaign.level(), &materials) {
+        error!("can't load {}: {}", LEVELS[campaign.current], error);
+        state.set(AppState::Menu).expect("enter Menu");
     }
 }
 
-fn step_velocity(
-    mut step_query: Query<(&Step, &Track, &Transform, &mut Velocity)>,
-    escalator_query: Query<(&Escalator, &Transform)>,
+/// Forgets the last attempt's progress, history and recording.
+fn start_run(
+    campaign: Res<Campaign>,
+    mut goal: ResMut<GoalReached>,
+    mut history: ResMut<History>,
+    record: Res<RecordPath>,
+    mut replay: ResMut<ReplayMode>,
 ) {
-    dbg!("step_velocity");
-    for (step, track, step_transform, mut velocity) in step_query.iter_mut() {
-        let (escalator, escalator_transform) = escalator_query
-            .get(step.escalator)
-            .expect("Step escalator lookup");
-
-        let s = step.length;
-        let n = escalator.length / s;
-
-        let t1 = s;
-        let t2 = s + (n - 1.) * s;
-        let t3 = 2. * s + (n - 1.) * s;
+    goal.0 = false;
+    history.clear();
 
-        let t = track.position;
-
-        let target = escalator_transform.translation.truncate() + {
-            if t < t1 {
-                Vec2::new(-(n - 3.0) * s / 2.0, (n - 1.0) * s / 2.0) + Vec2::new(-t, 0.0)
-            } else if t < t2 {
-                Vec2::new(-(n - 1.0) * s / 2.0, (n - 1.0) * s / 2.0) + Vec2::new(t - t1, -(t - t1))
-            } else if t < t3 {
-                Vec2::new((n - 1.) * s / 2., -(n - 1.) * s / 2.) + Vec2::new(t - t2, 0.0)
-            } else {
-                Vec2::new((n + 1.) * s / 2., -(n - 1.) * s / 2.) + Vec2::new(-(t - t3), t - t3)
-            }
-        };
-
-        *velocity = Velocity(target - step_transform.translation.truncate());
+    if record.0.is_some() {
+        *replay = ReplayMode::Record(Replay::new(LEVELS[campaign.current]));
     }
 }
 
-fn process_collisions(
-    time: Res<Time>,
-    q: Query<(Entity, &Transform, &ConvexPolygon), Without<Ladder>>,
-
-    mut velocities: Query<&mut Velocity>,
-
-    steps: Query<&Step>,
+fn save_replay(
+    keys: Res<Input<KeyCode>>,
+    record: Res<RecordPath>,
+    replay: Res<ReplayMode>,
+    bodies: Query<(&InLevel, &Transform), With<Velocity>>,
 ) {
-    // HACK: this will get multiplied by delta, so we divide by it first
-    let delta = BASE_SPEED_FACTOR * time.delta_seconds();
-
-    if delta == 0.0 {
-        return;
-    }
-
-    for (entity_a, xform_a, poly_a) in q.iter() {
-        for (entity_b, xform_b, poly_b) in q.iter() {
-            if entity_a >= entity_b {
-                continue;
-            }
-
-            if let Ok(step_a) = steps.get(entity_a) {
-                if step_a.escalator == entity_b {
-                    continue;
-                }
-
-                if let Ok(_step_b) = steps.get(entity_b) {
-                    continue;
-                }
-            }
-
-            if let Ok(step) = steps.get(entity_b) {
-                if step.escalator == entity_a {
-                    continue;
-                }
-            }
-
-            if let Some(contact) = collision(poly_a, &xform_a, poly_b, &xform_b) {
-                // HACK: collisions shouldn't push down(?)
-
-                if velocities.get_mut(entity_a).is_ok() && velocities.get_mut(entity_b).is_ok() {
-                    {
-                        let mut collision_correction = contact.normal1 * contact.dist;
-                        collision_correction.y = collision_correction.y.max(0.0);
-
-                        let mut velocity_a = velocities.get_mut(entity_a).unwrap();
-                        *velocity_a = Velocity(velocity_a.0 + collision_correction / delta);
-                    }
-
-                    {
-                        let mut collision_correction = contact.normal2 * contact.dist;
-                        collision_correction.y = collision_correction.y.max(0.0);
-
-                        let mut velocity_b = velocities.get_mut(entity_b).unwrap();
-                        *velocity_b = Velocity(velocity_b.0 + collision_correction / delta);
-                    }
-                } else if let Ok(mut w) = velocities.get_mut(entity_a) {
-                    let collision_correction = contact.normal1 * contact.dist;
-                    *w = Velocity(w.0 + collision_correction / delta);
-                } else if let Ok(mut r) = velocities.get_mut(entity_b) {
-                    let collision_correction: Vec2 = contact.normal2 * contact.dist;
-                    *r = Velocity(r.0 + collision_correction / delta);
-                } else {
-                }
-            }
+    if let (Some(path), ReplayMode::Record(replay)) = (&record.0, &*replay) {
+        if keys.just_pressed(SAVE_REPLAY_KEY) {
+            let replay = Replay {
+                expected: Some(body_positions(bodies.iter())),
+                ..replay.clone()
+            };
+            std::fs::write(path, replay.to_ron().expect("serialize replay"))
+                .expect("write replay file");
         }
     }
 }
 
-fn update_position(time: Res<Time>, mut query: Query<(&Velocity, &mut Transform)>) {
-    let delta = BASE_SPEED_FACTOR * time.delta_seconds();
-    for (velocity, mut transform) in query.iter_mut() {
-        transform.translation.x += delta * velocity.0.x;
-        transform.translation.y += delta * velocity.0.y;
-    }
-}
-
-fn update_step_track(time: Res<Time>, mut steps: Query<(&Step, &mut Track)>) {
-    let delta = BASE_SPEED_FACTOR * time.delta_seconds();
-
-    for (_step, mut track) in steps.iter_mut() {
-        track.position = (track.position + delta) % track.length;
+fn complete_level(goal: Res<GoalReached>, mut state: ResMut<State<AppState>>) {
+    if goal.0 {
+        state.set(AppState::LevelComplete).expect("enter LevelComplete");
     }
 }
 
-fn reset_velocity(mut query: Query<&mut Velocity>) {
-    dbg!("Resetting velocity");
-    for mut velocity in query.iter_mut() {
-        *velocity = Velocity(Vec2::ZERO);
+fn next_level(
+    keys: Res<Input<KeyCode>>,
+    mut campaign: ResMut<Campaign>,
+    mut state: ResMut<State<AppState>>,
+) {
+    if keys.just_pressed(CONTINUE_KEY) {
+        let next = if campaign.advance() {
+            AppState::Playing
+        } else {
+            AppState::Menu
+        };
+        state.set(next).expect("leave LevelComplete");
     }
 }
 
 fn lines(mut lines: ResMut<DebugLines>, q: Query<(&Transform, &ConvexPolygon)>) {
     for (xform, polygon) in q.iter() {
-        for (point1, point2) in polygon.points().iter().skip(1).zip(polygon.points()) {
-            let start = Vec3::new(
-                point1.x + xform.translation.x,
-                point1.y + xform.translation.y,
-                0.0,
-            );
-
-            let end = Vec3::new(
-                point2.x + xform.translation.x,
-                point2.y + xform.translation.y,
-                0.0,
-            );
-
-            lines.line(start, end, 0.0);
-        }
-
-        if let Some(point1) = polygon.points().first() {
-            if let Some(point2) = polygon.points().last() {
-                let start = Vec3::new(
-                    point1.x + xform.translation.x,
-                    point1.y + xform.translation.y,
-                    0.0,
-                );
-
-                let end = Vec3::new(
-                    point2.x + xform.translation.x,
-                    point2.y + xform.translation.y,
-                    0.0,
-                );
-
-                lines.line(start, end, 0.0);
-            }
-        }
-    }
-}
-
-#[derive(Debug, Clone)]
-struct BevyCollision {
-    normal1: Vec2,
-    normal2: Vec2,
-    dist: f32,
-}
-
-fn collision(
-    poly1: &ConvexPolygon,
-    xform1: &Transform,
-    poly2: &ConvexPolygon,
-    xform2: &Transform,
-) -> Option<BevyCollision> {
-    let p1 = Vector2::new(xform1.translation.x, xform1.translation.y);
-    let i1 = Isometry2::new(p1, 0.0);
-
-    let p2 = Vector2::new(xform2.translation.x, xform2.translation.y);
-    let i2 = Isometry2::new(p2, 0.0);
-
-    let epsilon = 0.0001;
-    query::contact(&i1, poly1, &i2, poly2, 0.1)
-        .map(|contact| {
-            contact.map(|contact| {
-                if contact.dist >= epsilon {
-                    return None;
-                }
-
-                Some(BevyCollision {
-                    normal1: Vec2::new(contact.normal1.x, contact.normal1.y),
-                    normal2: Vec2::new(contact.normal2.x, contact.normal2.y),
-                    dist: contact.dist,
-                })
-            })
-        })
-        .ok()
-        .flatten()
-        .flatten()
-}
-
-const LADDER_TOLERANCE: f32 = 2.0;
-
-fn ladder(
-    keys: Res<Input<KeyCode>>,
-
-    mut players: Query<(&Player, &Transform, &ConvexPolygon, &mut Velocity)>,
-    ladders: Query<(&Ladder, &Transform, &ConvexPolygon)>,
-) {
-    for (_player, player_xform, player_poly, mut player_velocity) in players.iter_mut() {
-        for (_ladder, ladder_xform, ladder_poly) in ladders.iter() {
-            if let Some(_collision) =
-                collision(player_poly, player_xform, ladder_poly, ladder_xform)
-            {
-                if (player_xform.translation.x - ladder_xform.translation.x).abs()
-                    < LADDER_TOLERANCE
-                    && keys.pressed(KeyCode::W)
-                {
-                    player_velocity.0.x = ladder_xform.translation.x - player_xform.translation.x;
-                    player_velocity.0.y = 1.0;
-                }
-            }
+        let points = world_points(xform, polygon);
+        for (point1, point2) in points.iter().zip(points.iter().cycle().skip(1)) {
+            lines.line(point1.extend(0.0), point2.extend(0.0), 0.0);
         }
     }
 }
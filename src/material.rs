@@ -0,0 +1,59 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Surface properties of a body, mixed with whatever it touches.
+///
+/// Bodies without a `PhysicsMaterial` use the default: full friction and no
+/// bounce.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PhysicsMaterial {
+    /// How much of its support's motion a rider picks up, and how hard it
+    /// grips: at `1.0` it holds on slopes up to 45 degrees. At `0.0` nothing
+    /// is carried and nothing stops it sliding.
+    pub friction: f32,
+    /// How much of the speed of an impact is thrown back, from `0.0` for a
+    /// dead stop to `1.0` for a full rebound.
+    pub restitution: f32,
+}
+
+impl PhysicsMaterial {
+    pub const ICE: PhysicsMaterial = PhysicsMaterial {
+        friction: 0.0,
+        restitution: 0.0,
+    };
+    pub const STICKY: PhysicsMaterial = PhysicsMaterial {
+        friction: 4.0,
+        restitution: 0.0,
+    };
+    pub const BOUNCY: PhysicsMaterial = PhysicsMaterial {
+        friction: 1.0,
+        restitution: 0.8,
+    };
+
+    /// The material of a contact between `self` and `other`: the geometric
+    /// mean of their frictions, so ice is slippery against anything, and the
+    /// bouncier of their restitutions.
+    pub fn mix(self, other: PhysicsMaterial) -> PhysicsMaterial {
+        PhysicsMaterial {
+            friction: (self.friction * other.friction).sqrt(),
+            restitution: self.restitution.max(other.restitution),
+        }
+    }
+
+    /// The mixed material for a pair of bodies, either of which may have no
+    /// material of its own.
+    pub fn between(materials: &Query<&PhysicsMaterial>, a: Entity, b: Entity) -> PhysicsMaterial {
+        let material = |entity| materials.get(entity).copied().unwrap_or_default();
+        material(a).mix(material(b))
+    }
+}
+
+impl Default for PhysicsMaterial {
+    fn default() -> Self {
+        PhysicsMaterial {
+            friction: 1.0,
+            restitution: 0.0,
+        }
+    }
+}
@@ -0,0 +1,119 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{velocity_towards, Velocity, DELTA};
+
+/// What a body does on reaching the last waypoint of its `Path`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PathMode {
+    /// Carry on back to the first waypoint, and round again.
+    Loop,
+    /// Turn around and retrace the path back to the first waypoint.
+    PingPong,
+}
+
+impl Default for PathMode {
+    fn default() -> Self {
+        PathMode::PingPong
+    }
+}
+
+/// A route through world-space waypoints for a body to follow, the way steps
+/// follow their escalator's track: elevators, shuttles, moving platforms.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Path {
+    pub waypoints: Vec<Vec2>,
+    /// In units of `Velocity`.
+    pub speed: f32,
+    pub mode: PathMode,
+}
+
+/// How far along its `Path` a body is, and which way it's heading.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct PathProgress {
+    pub distance: f32,
+    pub reversing: bool,
+}
+
+impl Path {
+    fn segments(&self) -> impl Iterator<Item = (Vec2, Vec2)> + '_ {
+        let closing = match self.mode {
+            PathMode::Loop => self.waypoints.first(),
+            PathMode::PingPong => None,
+        };
+
+        self.waypoints
+            .iter()
+            .copied()
+            .zip(self.waypoints.iter().skip(1).chain(closing).copied())
+    }
+
+    /// Distance from the first waypoint round to where the path ends; for a
+    /// loop, that's back at the first waypoint.
+    pub fn length(&self) -> f32 {
+        self.segments()
+            .map(|(start, end)| start.distance(end))
+            .sum()
+    }
+
+    pub fn point_at(&self, distance: f32) -> Vec2 {
+        let mut remaining = distance;
+        for (start, end) in self.segments() {
+            let length = start.distance(end);
+            if remaining <= length && length > 0.0 {
+                return start + (end - start) * (remaining / length);
+            }
+            remaining -= length;
+        }
+
+        match self.mode {
+            PathMode::Loop => self.waypoints.first(),
+            PathMode::PingPong => self.waypoints.last(),
+        }
+        .copied()
+        .unwrap_or(Vec2::ZERO)
+    }
+
+    /// Moves `progress` on by one tick at `speed`.
+    pub fn advance(&self, progress: &mut PathProgress) {
+        let length = self.length();
+        if length <= 0.0 {
+            return;
+        }
+
+        let step = self.speed * DELTA;
+        match self.mode {
+            PathMode::Loop => {
+                progress.distance = (progress.distance + step).rem_euclid(length);
+            }
+            PathMode::PingPong => {
+                if progress.reversing {
+                    progress.distance -= step;
+                    if progress.distance <= 0.0 {
+                        progress.distance = -progress.distance;
+                        progress.reversing = false;
+                    }
+                } else {
+                    progress.distance += step;
+                    if progress.distance >= length {
+                        progress.distance = 2.0 * length - progress.distance;
+                        progress.reversing = true;
+                    }
+                }
+                progress.distance = progress.distance.clamp(0.0, length);
+            }
+        }
+    }
+}
+
+pub fn update_path_progress(mut bodies: Query<(&Path, &mut PathProgress)>) {
+    for (path, mut progress) in bodies.iter_mut() {
+        path.advance(&mut progress);
+    }
+}
+
+pub fn path_velocity(mut bodies: Query<(&Path, &PathProgress, &Transform, &mut Velocity)>) {
+    for (path, progress, xform, mut velocity) in bodies.iter_mut() {
+        *velocity = velocity_towards(path.point_at(progress.distance), xform);
+    }
+}
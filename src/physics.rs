@@ -0,0 +1,269 @@
+use std::collections::BTreeMap;
+
+use bevy::prelude::*;
+use nalgebra::{Isometry2, Vector2};
+use parry2d::{query, shape::ConvexPolygon};
+
+use crate::{
+    Contacts, Escalator, Grabbing, Mass, Momentum, PhysicsMaterial, Player, Step, Support, Track,
+    Velocity, DELTA, GRAB_SPEED,
+};
+
+/// Momentum above this, into a surface, counts as an impact and can bounce.
+pub const BOUNCE_THRESHOLD: f32 = 0.5;
+
+/// What's left of `momentum` after pressing into a surface along `normal`,
+/// which points into the surface.
+fn constrain(momentum: Vec2, normal: Vec2, material: PhysicsMaterial) -> Vec2 {
+    let into = momentum.dot(normal);
+    if into <= 0.0 {
+        return momentum;
+    }
+
+    let bounce = if into > BOUNCE_THRESHOLD {
+        material.restitution
+    } else {
+        0.0
+    };
+    let mut momentum = momentum - (1.0 + bounce) * into * normal;
+
+    // friction can only take away as much sliding as the surface pushes
+    // back, so bodies slip down slopes steeper than their friction allows
+    let tangent = normal.perp();
+    let sliding = momentum.dot(tangent);
+    let grip = (material.friction * into).min(sliding.abs());
+    momentum -= grip * sliding.signum() * tangent;
+
+    momentum
+}
+
+/*
+The normal force stops bodies moving into whatever they touch, so resting
+bodies don't gather speed under gravity. Hard enough impacts bounce back
+according to the pair's restitution.
+*/
+pub fn normal_force(
+    contacts: Res<Contacts>,
+    materials: Query<&PhysicsMaterial>,
+    mut momenta: Query<&mut Momentum>,
+) {
+    for (entity_a, entity_b, contact) in contacts.solid() {
+        let material = PhysicsMaterial::between(&materials, entity_a, entity_b);
+
+        if let Ok(mut momentum_a) = momenta.get_mut(entity_a) {
+            momentum_a.0 = constrain(momentum_a.0, contact.normal1, material);
+        }
+
+        if let Ok(mut momentum_b) = momenta.get_mut(entity_b) {
+            momentum_b.0 = constrain(momentum_b.0, contact.normal2, material);
+        }
+    }
+}
+
+pub fn player_velocity(
+    keyboard_input: Res<Input<KeyCode>>,
+    contacts: Res<Contacts>,
+    mut query: Query<(Entity, &Player, &Support, &Grabbing, &mut Velocity)>,
+) {
+    for (entity, _player, support, grabbing, mut velocity) in query.iter_mut() {
+        // walk along whatever we're standing on, so a slope is climbed
+        // rather than pushed into, and walked down rather than off
+        let forward = support
+            .0
+            .and_then(|support| contacts.normal(entity, support))
+            .map_or(Vec2::X, |normal| normal.perp());
+        let speed = if grabbing.0.is_some() {
+            GRAB_SPEED
+        } else {
+            1.0
+        };
+
+        if keyboard_input.pressed(KeyCode::A) {
+            velocity.0 -= forward * speed;
+        }
+        if keyboard_input.pressed(KeyCode::D) {
+            velocity.0 += forward * speed;
+        }
+    }
+}
+
+/// The velocity that puts a body exactly on `target` at the end of this tick.
+pub fn velocity_towards(target: Vec2, xform: &Transform) -> Velocity {
+    Velocity((target - xform.translation.truncate()) / DELTA)
+}
+
+pub fn step_velocity(
+    mut step_query: Query<(&Step, &Track, &Transform, &mut Velocity)>,
+    escalator_query: Query<(&Escalator, &Transform)>,
+) {
+    for (step, track, step_transform, mut velocity) in step_query.iter_mut() {
+        let (escalator, escalator_transform) = escalator_query
+            .get(step.escalator)
+            .expect("Step escalator lookup");
+
+        let s = step.length;
+        let n = escalator.length / s;
+
+        let t1 = s;
+        let t2 = s + (n - 1.) * s;
+        let t3 = 2. * s + (n - 1.) * s;
+
+        let t = track.position;
+
+        let offset = escalator.facing.orient(if t < t1 {
+            Vec2::new(-(n - 3.0) * s / 2.0, (n - 1.0) * s / 2.0) + Vec2::new(-t, 0.0)
+        } else if t < t2 {
+            Vec2::new(-(n - 1.0) * s / 2.0, (n - 1.0) * s / 2.0) + Vec2::new(t - t1, -(t - t1))
+        } else if t < t3 {
+            Vec2::new((n - 1.) * s / 2., -(n - 1.) * s / 2.) + Vec2::new(t - t2, 0.0)
+        } else {
+            Vec2::new((n + 1.) * s / 2., -(n - 1.) * s / 2.) + Vec2::new(-(t - t3), t - t3)
+        });
+        let target = (escalator_transform.translation
+            + escalator_transform.rotation * offset.extend(0.0))
+        .truncate();
+
+        // land exactly on the target this tick, so steps keep pace with
+        // their track at any escalator speed and stop dead when it stops
+        *velocity = velocity_towards(target, step_transform);
+    }
+}
+
+fn inverse_mass(
+    entity: Entity,
+    masses: &Query<&Mass>,
+    velocities: &mut Query<&mut Velocity>,
+) -> f32 {
+    if velocities.get_mut(entity).is_err() {
+        return 0.0;
+    }
+
+    masses.get(entity).map_or(0.0, |mass| mass.inverse())
+}
+
+/*
+Overlapping bodies get an impulse along the contact normal that separates
+them within one tick. It's split by inverse mass, so the lighter body
+moves further and a body with infinite mass doesn't move at all.
+
+Contacts aren't regenerated between passes. Each pass works out how much
+of each overlap is left from how far the previous passes have pushed the
+two bodies along its normal, and every push in a pass is worked out from
+where the pass started.
+*/
+pub fn process_collisions(
+    mut contacts: ResMut<Contacts>,
+    masses: Query<&Mass>,
+    mut velocities: Query<&mut Velocity>,
+) {
+    let mut pushes: BTreeMap<Entity, Vec2> = BTreeMap::new();
+
+    for (entity_a, entity_b, contact) in contacts.solid() {
+        let inverse_mass_a = inverse_mass(entity_a, &masses, &mut velocities);
+        let inverse_mass_b = inverse_mass(entity_b, &masses, &mut velocities);
+
+        let total = inverse_mass_a + inverse_mass_b;
+        if total == 0.0 {
+            continue;
+        }
+
+        let dist = contact.dist
+            + (contacts.offset(entity_b) - contacts.offset(entity_a)).dot(contact.normal1);
+        if dist >= 0.0 {
+            continue;
+        }
+
+        // `dist` is negative while overlapping, so these point away from the other body
+        *pushes.entry(entity_a).or_default() += contact.normal1 * dist * (inverse_mass_a / total);
+        *pushes.entry(entity_b).or_default() += contact.normal2 * dist * (inverse_mass_b / total);
+    }
+
+    for (entity, push) in pushes {
+        if let Ok(mut velocity) = velocities.get_mut(entity) {
+            velocity.0 += push / DELTA;
+            *contacts.offsets.entry(entity).or_default() += push;
+        }
+    }
+}
+
+pub fn update_position(mut query: Query<(&Velocity, &mut Transform)>) {
+    for (velocity, mut transform) in query.iter_mut() {
+        transform.translation.x += DELTA * velocity.0.x;
+        transform.translation.y += DELTA * velocity.0.y;
+    }
+}
+
+pub fn update_step_track(mut steps: Query<(&Step, &mut Track)>, escalators: Query<&Escalator>) {
+    for (step, mut track) in steps.iter_mut() {
+        let escalator = escalators
+            .get(step.escalator)
+            .expect("Step escalator lookup");
+
+        track.position = (track.position + escalator.track_delta()).rem_euclid(track.length);
+    }
+}
+
+pub fn reset_velocity(mut query: Query<&mut Velocity>) {
+    for mut velocity in query.iter_mut() {
+        *velocity = Velocity(Vec2::ZERO);
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct BevyCollision {
+    pub normal1: Vec2,
+    pub normal2: Vec2,
+    pub dist: f32,
+}
+
+/// Rotation about z, in radians.
+pub fn angle(xform: &Transform) -> f32 {
+    let x = xform.rotation * Vec3::X;
+    x.y.atan2(x.x)
+}
+
+/// A collider's vertices in world space, rotated with its transform.
+pub fn world_points(xform: &Transform, poly: &ConvexPolygon) -> Vec<Vec2> {
+    poly.points()
+        .iter()
+        .map(|point| {
+            (xform.translation + xform.rotation * Vec3::new(point.x, point.y, 0.0)).truncate()
+        })
+        .collect()
+}
+
+fn isometry(xform: &Transform) -> Isometry2<f32> {
+    Isometry2::new(
+        Vector2::new(xform.translation.x, xform.translation.y),
+        angle(xform),
+    )
+}
+
+pub(crate) fn collision(
+    poly1: &ConvexPolygon,
+    xform1: &Transform,
+    poly2: &ConvexPolygon,
+    xform2: &Transform,
+) -> Option<BevyCollision> {
+    let i1 = isometry(xform1);
+    let i2 = isometry(xform2);
+
+    let epsilon = 0.0001;
+    query::contact(&i1, poly1, &i2, poly2, 0.1)
+        .map(|contact| {
+            contact.map(|contact| {
+                if contact.dist >= epsilon {
+                    return None;
+                }
+
+                Some(BevyCollision {
+                    normal1: Vec2::new(contact.normal1.x, contact.normal1.y),
+                    normal2: Vec2::new(contact.normal2.x, contact.normal2.y),
+                    dist: contact.dist,
+                })
+            })
+        })
+        .ok()
+        .flatten()
+        .flatten()
+}
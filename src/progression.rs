@@ -0,0 +1,79 @@
+use bevy::prelude::*;
+
+use crate::{Contacts, Crate, Level, Player, Switch};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AppState {
+    Menu,
+    Playing,
+    LevelComplete,
+}
+
+/// The level is won when the player overlaps an exit.
+pub struct Exit;
+
+/// A switch that is on while a `Crate` overlaps it. The level is won once
+/// every target pad is on.
+pub struct TargetPad;
+
+/// Whether the loaded level's win condition held on the last tick.
+#[derive(Debug, Default)]
+pub struct GoalReached(pub bool);
+
+/// The ordered levels to play through, and which of them is loaded.
+pub struct Campaign {
+    pub levels: Vec<Level>,
+    pub current: usize,
+}
+
+impl Campaign {
+    pub fn new(levels: Vec<Level>) -> Self {
+        Campaign { levels, current: 0 }
+    }
+
+    pub fn level(&self) -> &Level {
+        &self.levels[self.current]
+    }
+
+    /// Moves on to the next level. Returns `false`, and starts over from the
+    /// first level, once the last one has been beaten.
+    pub fn advance(&mut self) -> bool {
+        self.current += 1;
+        if self.current < self.levels.len() {
+            true
+        } else {
+            self.current = 0;
+            false
+        }
+    }
+}
+
+pub fn target_pads(
+    contacts: Res<Contacts>,
+    mut pads: Query<(Entity, &mut Switch), With<TargetPad>>,
+    crates: Query<Entity, With<Crate>>,
+) {
+    for (pad, mut switch) in pads.iter_mut() {
+        switch.on = contacts
+            .touching(pad)
+            .any(|other| crates.get(other).is_ok());
+    }
+}
+
+/// A level is won when the player reaches an exit, or when it has target
+/// pads and every one of them holds a crate.
+pub fn check_goal(
+    mut goal: ResMut<GoalReached>,
+    contacts: Res<Contacts>,
+    players: Query<Entity, With<Player>>,
+    exits: Query<Entity, With<Exit>>,
+    pads: Query<&Switch, With<TargetPad>>,
+) {
+    let at_exit = players
+        .iter()
+        .any(|player| exits.iter().any(|exit| contacts.contains(player, exit)));
+
+    let pads_held = pads.iter().next().is_some() && pads.iter().all(|pad| pad.on);
+
+    goal.0 = at_exit || pads_held;
+}
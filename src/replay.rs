@@ -0,0 +1,122 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::InLevel;
+
+/// The keys held on every tick of a run, as saved in a `.ron` replay file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Replay {
+    /// Path of the level file the run was recorded on.
+    pub level: String,
+    pub inputs: Vec<HeldKeys>,
+    /// Where `body_positions` says every body ended up, for golden tests.
+    #[serde(default)]
+    pub expected: Option<Vec<Vec2>>,
+}
+
+/// Exactly `keys` held down for `ticks` consecutive ticks.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HeldKeys {
+    pub keys: Vec<KeyCode>,
+    pub ticks: usize,
+}
+
+impl Replay {
+    pub fn new(level: impl Into<String>) -> Self {
+        Replay {
+            level: level.into(),
+            inputs: vec![],
+            expected: None,
+        }
+    }
+
+    pub fn from_ron(source: &str) -> Result<Replay, ron::Error> {
+        ron::from_str(source)
+    }
+
+    pub fn to_ron(&self) -> Result<String, ron::Error> {
+        ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+    }
+
+    /// Appends one tick with `keys` held.
+    pub fn record(&mut self, keys: Vec<KeyCode>) {
+        match self.inputs.last_mut() {
+            Some(held) if held.keys == keys => held.ticks += 1,
+            _ => self.inputs.push(HeldKeys { keys, ticks: 1 }),
+        }
+    }
+
+    pub fn ticks(&self) -> usize {
+        self.inputs.iter().map(|held| held.ticks).sum()
+    }
+
+    pub fn keys_at(&self, mut tick: usize) -> Option<&[KeyCode]> {
+        for held in self.inputs.iter() {
+            if tick < held.ticks {
+                return Some(&held.keys);
+            }
+            tick -= held.ticks;
+        }
+        None
+    }
+}
+
+/// Where every moving body is, in the order they were spawned.
+pub fn body_positions<'a>(bodies: impl Iterator<Item = (&'a InLevel, &'a Transform)>) -> Vec<Vec2> {
+    let mut bodies: Vec<_> = bodies.collect();
+    bodies.sort_by_key(|(in_level, _)| **in_level);
+    bodies
+        .into_iter()
+        .map(|(_, transform)| transform.translation.truncate())
+        .collect()
+}
+
+/// Whether ticks are being recorded to, or played back from, a `Replay`.
+pub enum ReplayMode {
+    Off,
+    Record(Replay),
+    Play { replay: Replay, tick: usize },
+}
+
+impl Default for ReplayMode {
+    fn default() -> Self {
+        ReplayMode::Off
+    }
+}
+
+/// Releases every key not in `keys` and presses every key that is.
+pub fn hold_exactly(input: &mut Input<KeyCode>, keys: &[KeyCode]) {
+    for key in input.get_pressed().copied().collect::<Vec<_>>() {
+        if !keys.contains(&key) {
+            input.release(key);
+        }
+    }
+    for key in keys {
+        input.press(*key);
+    }
+}
+
+/// Runs at the start of each tick, before anything reads `Input<KeyCode>`:
+/// records the held keys, or replaces them with the replay's.
+///
+/// Once a replay runs out, no keys are held.
+pub fn replay_input(world: &mut World) {
+    world.resource_scope(|world, mut mode: Mut<ReplayMode>| {
+        let mut input = world
+            .get_resource_mut::<Input<KeyCode>>()
+            .expect("Input<KeyCode> resource");
+
+        match &mut *mode {
+            ReplayMode::Off => {}
+            ReplayMode::Record(replay) => {
+                let mut keys: Vec<_> = input.get_pressed().copied().collect();
+                keys.sort();
+                replay.record(keys);
+            }
+            ReplayMode::Play { replay, tick } => {
+                hold_exactly(&mut input, replay.keys_at(*tick).unwrap_or(&[]));
+                *tick += 1;
+            }
+        }
+    });
+}
@@ -0,0 +1,271 @@
+use std::collections::{BTreeSet, VecDeque};
+
+use bevy::prelude::*;
+use parry2d::shape::ConvexPolygon;
+
+use crate::{
+    AngularVelocity, Climbing, CollisionLayers, Contacts, Conveyor, Door, Escalator,
+    EscalatorState, Grabbing, Jump, Lever, Momentum, PathProgress, Switch, Track, Velocity,
+};
+
+/// Key that, while held, steps the simulation back one tick per tick.
+pub const REWIND_KEY: KeyCode = KeyCode::Z;
+
+/// Ticks of history kept for rewinding; ten seconds at `TIMESTEP`.
+pub const HISTORY_LENGTH: usize = 600;
+
+struct BodyState {
+    entity: Entity,
+    transform: Transform,
+    velocity: Option<Velocity>,
+    momentum: Option<Momentum>,
+    angular_velocity: Option<AngularVelocity>,
+    jump: Option<Jump>,
+    climbing: Option<Climbing>,
+    grabbing: Option<Grabbing>,
+    layers: Option<CollisionLayers>,
+    track_position: Option<f32>,
+    path_progress: Option<PathProgress>,
+    switch_on: Option<bool>,
+    lever_held: Option<bool>,
+    door_open: Option<bool>,
+    escalator_state: Option<EscalatorState>,
+    conveyor_state: Option<EscalatorState>,
+}
+
+/// The state of every moving, switchable or powered entity at the end of one tick.
+pub struct Snapshot {
+    bodies: Vec<BodyState>,
+    /// What `Contacts` last saw touching, so collision events resume from
+    /// this tick rather than reporting the jump back as pairs starting and
+    /// ending.
+    touching: BTreeSet<(Entity, Entity)>,
+}
+
+impl Snapshot {
+    pub fn take(world: &mut World) -> Self {
+        let mut query = world.query_filtered::<(
+            Entity,
+            &Transform,
+            (
+                Option<&Velocity>,
+                Option<&Momentum>,
+                Option<&AngularVelocity>,
+                Option<&Jump>,
+                Option<&Climbing>,
+                Option<&Grabbing>,
+                Option<&CollisionLayers>,
+            ),
+            (
+                Option<&Track>,
+                Option<&PathProgress>,
+                Option<&Switch>,
+                Option<&Lever>,
+                Option<&Door>,
+                Option<&Escalator>,
+                Option<&Conveyor>,
+            ),
+        ), Or<(
+            With<Velocity>,
+            With<Track>,
+            With<Switch>,
+            With<Door>,
+            With<Escalator>,
+            With<Conveyor>,
+        )>>();
+
+        let bodies = query
+            .iter(world)
+            .map(
+                |(
+                    entity,
+                    transform,
+                    (velocity, momentum, angular_velocity, jump, climbing, grabbing, layers),
+                    (track, path_progress, switch, lever, door, escalator, conveyor),
+                )| {
+                    BodyState {
+                        entity,
+                        transform: *transform,
+                        velocity: velocity.cloned(),
+                        momentum: momentum.copied(),
+                        angular_velocity: angular_velocity.copied(),
+                        jump: jump.copied(),
+                        climbing: climbing.copied(),
+                        grabbing: grabbing.copied(),
+                        layers: layers.copied(),
+                        track_position: track.map(|track| track.position),
+                        path_progress: path_progress.copied(),
+                        switch_on: switch.map(|switch| switch.on),
+                        lever_held: lever.map(|lever| lever.held),
+                        door_open: door.map(|door| door.open),
+                        escalator_state: escalator.map(|escalator| escalator.state),
+                        conveyor_state: conveyor.map(|conveyor| conveyor.state),
+                    }
+                },
+            )
+            .collect();
+
+        Snapshot {
+            bodies,
+            touching: world
+                .get_resource::<Contacts>()
+                .map(|contacts| contacts.previous.clone())
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Writes this snapshot back, skipping entities that have since been despawned.
+    pub fn restore(&self, world: &mut World) {
+        if let Some(mut contacts) = world.get_resource_mut::<Contacts>() {
+            contacts.pairs.clear();
+            contacts.previous = self.touching.clone();
+        }
+
+        for body in self.bodies.iter() {
+            if let Some(mut transform) = world.get_mut::<Transform>(body.entity) {
+                *transform = body.transform;
+            }
+            if let (Some(velocity), Some(mut current)) =
+                (&body.velocity, world.get_mut::<Velocity>(body.entity))
+            {
+                *current = velocity.clone();
+            }
+            if let (Some(momentum), Some(mut current)) =
+                (body.momentum, world.get_mut::<Momentum>(body.entity))
+            {
+                *current = momentum;
+            }
+            if let (Some(angular_velocity), Some(mut current)) = (
+                body.angular_velocity,
+                world.get_mut::<AngularVelocity>(body.entity),
+            ) {
+                *current = angular_velocity;
+            }
+            if let (Some(jump), Some(mut current)) = (body.jump, world.get_mut::<Jump>(body.entity))
+            {
+                *current = jump;
+            }
+            if let (Some(climbing), Some(mut current)) =
+                (body.climbing, world.get_mut::<Climbing>(body.entity))
+            {
+                *current = climbing;
+            }
+            if let (Some(grabbing), Some(mut current)) =
+                (body.grabbing, world.get_mut::<Grabbing>(body.entity))
+            {
+                *current = grabbing;
+            }
+            // climbing changes what the player collides with
+            if let (Some(layers), Some(mut current)) =
+                (body.layers, world.get_mut::<CollisionLayers>(body.entity))
+            {
+                *current = layers;
+            }
+            if let (Some(position), Some(mut track)) =
+                (body.track_position, world.get_mut::<Track>(body.entity))
+            {
+                track.position = position;
+            }
+            if let (Some(path_progress), Some(mut current)) = (
+                body.path_progress,
+                world.get_mut::<PathProgress>(body.entity),
+            ) {
+                *current = path_progress;
+            }
+            if let (Some(on), Some(mut switch)) =
+                (body.switch_on, world.get_mut::<Switch>(body.entity))
+            {
+                switch.on = on;
+            }
+            if let (Some(held), Some(mut lever)) =
+                (body.lever_held, world.get_mut::<Lever>(body.entity))
+            {
+                lever.held = held;
+            }
+            if let (Some(state), Some(mut escalator)) = (
+                body.escalator_state,
+                world.get_mut::<Escalator>(body.entity),
+            ) {
+                escalator.state = state;
+            }
+            if let (Some(state), Some(mut conveyor)) =
+                (body.conveyor_state, world.get_mut::<Conveyor>(body.entity))
+            {
+                conveyor.state = state;
+            }
+            if let Some(open) = body.door_open {
+                set_door(world, body.entity, open);
+            }
+        }
+    }
+}
+
+/// Opens or closes a door, and its collider with it, as `power_doors` does.
+fn set_door(world: &mut World, entity: Entity, open: bool) {
+    let shape = match world.get_mut::<Door>(entity) {
+        Some(mut door) if door.open != open => {
+            door.open = open;
+            door.shape.clone()
+        }
+        _ => return,
+    };
+
+    if let Some(mut visible) = world.get_mut::<Visible>(entity) {
+        visible.is_visible = !open;
+    }
+    if open {
+        world.entity_mut(entity).remove::<ConvexPolygon>();
+    } else {
+        world.entity_mut(entity).insert(shape);
+    }
+}
+
+/// A ring buffer of the last `capacity` ticks, newest at the back.
+pub struct History {
+    pub snapshots: VecDeque<Snapshot>,
+    pub capacity: usize,
+}
+
+impl Default for History {
+    fn default() -> Self {
+        History {
+            snapshots: VecDeque::with_capacity(HISTORY_LENGTH),
+            capacity: HISTORY_LENGTH,
+        }
+    }
+}
+
+impl History {
+    pub fn clear(&mut self) {
+        self.snapshots.clear();
+    }
+}
+
+/// Runs at the end of each tick: records it, or, while `REWIND_KEY` is held,
+/// throws it away and restores the tick before the last one recorded.
+///
+/// Letting physics run and then overwriting it keeps the pipeline ungated;
+/// releasing the key resumes from whichever snapshot was restored last.
+pub fn rewind(world: &mut World) {
+    let rewinding = world
+        .get_resource::<Input<KeyCode>>()
+        .map_or(false, |keys| keys.pressed(REWIND_KEY));
+
+    world.resource_scope(|world, mut history: Mut<History>| {
+        if rewinding {
+            // never pop the oldest snapshot; it's where rewinding bottoms out
+            if history.snapshots.len() > 1 {
+                history.snapshots.pop_back();
+            }
+            if let Some(snapshot) = history.snapshots.back() {
+                snapshot.restore(world);
+            }
+        } else {
+            let snapshot = Snapshot::take(world);
+            if history.snapshots.len() >= history.capacity {
+                history.snapshots.pop_front();
+            }
+            history.snapshots.push_back(snapshot);
+        }
+    });
+}
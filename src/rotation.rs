@@ -0,0 +1,85 @@
+use std::f32::consts::FRAC_PI_2;
+
+use bevy::prelude::*;
+use parry2d::shape::ConvexPolygon;
+
+use crate::{aabb, angle, Contacts, Support, DELTA};
+
+/// Spin gained per tick by a body leaning out over the edge of its support.
+pub const TIP_ACCELERATION: f32 = 0.002;
+
+/// Fraction of the way to lying flat that a resting body turns each tick.
+pub const SETTLE_RATE: f32 = 0.2;
+
+/// Spin about z, in radians per unit of time like `Velocity`. Carries over
+/// from tick to tick, like `Momentum`.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct AngularVelocity(pub f32);
+
+/*
+Bodies tip over edges and settle onto their faces.
+A body whose center hangs past the end of everything it rests on spins
+towards the drop. One that's resting squarely turns to lie flat on its
+nearest face, along the slope of what it rests on, and friction takes
+away any other spin. In the air it keeps spinning as it was.
+*/
+pub fn tumble(
+    contacts: Res<Contacts>,
+    colliders: Query<(&Transform, &ConvexPolygon)>,
+    mut bodies: Query<(
+        Entity,
+        &Transform,
+        &ConvexPolygon,
+        &Support,
+        &mut AngularVelocity,
+    )>,
+) {
+    for (entity, xform, poly, support, mut angular) in bodies.iter_mut() {
+        let support = match support.0 {
+            Some(support) => support,
+            None => continue,
+        };
+
+        // the stretch of x held up by whatever the body rests on
+        let (min, max) = aabb(xform, poly);
+        let (left, right) = contacts
+            .touching(entity)
+            .filter(|&other| {
+                contacts
+                    .normal(entity, other)
+                    .map_or(false, |normal| normal.y < 0.0)
+            })
+            .filter_map(|other| colliders.get(other).ok())
+            .map(|(support_xform, support_poly)| aabb(support_xform, support_poly))
+            .fold(
+                (f32::INFINITY, f32::NEG_INFINITY),
+                |(left, right), (support_min, support_max)| {
+                    (
+                        left.min(min.x.max(support_min.x)),
+                        right.max(max.x.min(support_max.x)),
+                    )
+                },
+            );
+
+        let center = xform.translation.x;
+        if center > right {
+            angular.0 -= TIP_ACCELERATION;
+        } else if center < left {
+            angular.0 += TIP_ACCELERATION;
+        } else {
+            let surface = contacts.normal(entity, support).map_or(0.0, |normal| {
+                let tangent = normal.perp();
+                tangent.y.atan2(tangent.x)
+            });
+            let angle = angle(xform);
+            let flat = surface + ((angle - surface) / FRAC_PI_2).round() * FRAC_PI_2;
+            angular.0 = (flat - angle) * SETTLE_RATE / DELTA;
+        }
+    }
+}
+
+pub fn update_rotation(mut query: Query<(&AngularVelocity, &mut Transform)>) {
+    for (angular, mut transform) in query.iter_mut() {
+        transform.rotation = Quat::from_rotation_z(DELTA * angular.0) * transform.rotation;
+    }
+}
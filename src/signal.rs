@@ -0,0 +1,145 @@
+use bevy::prelude::*;
+use parry2d::shape::ConvexPolygon;
+
+use crate::{Contacts, Conveyor, Crate, Escalator, EscalatorState, Player};
+
+type Bodies<'w> = Query<'w, Entity, Or<(With<Crate>, With<Player>)>>;
+
+/// Key that flips a lever the player is touching.
+pub const LEVER_KEY: KeyCode = KeyCode::E;
+
+/// The on/off output of a pressure plate or lever.
+#[derive(Debug, Default)]
+pub struct Switch {
+    pub on: bool,
+}
+
+/// A switch that is on while a `Crate` or `Player` overlaps it.
+pub struct PressurePlate;
+
+/// A switch the player flips by pressing `LEVER_KEY` while touching it.
+#[derive(Debug, Default)]
+pub struct Lever {
+    /// Whether `LEVER_KEY` was down last tick, so holding it flips only once.
+    pub held: bool,
+}
+
+/// Wiring from one or more switches; powered while any of them is on.
+///
+/// A powered `Escalator` or `Conveyor` moves as its `PoweredState` says and
+/// an unpowered one stops; a powered `Door` opens.
+pub struct Powered {
+    pub sources: Vec<Entity>,
+}
+
+/// How an escalator or conveyor moves while powered: as it was authored, so
+/// a reversing one keeps reversing, or running if it was authored stopped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PoweredState(pub EscalatorState);
+
+impl PoweredState {
+    pub fn new(authored: EscalatorState) -> Self {
+        PoweredState(match authored {
+            EscalatorState::Stopped => EscalatorState::Running,
+            state => state,
+        })
+    }
+}
+
+/// A wall that drops its collider while open.
+pub struct Door {
+    pub open: bool,
+    /// The collider to restore when the door closes.
+    pub shape: ConvexPolygon,
+}
+
+pub fn pressure_plates(
+    contacts: Res<Contacts>,
+    mut plates: Query<(Entity, &mut Switch), With<PressurePlate>>,
+    bodies: Bodies,
+) {
+    for (plate, mut switch) in plates.iter_mut() {
+        switch.on = contacts
+            .touching(plate)
+            .any(|other| bodies.get(other).is_ok());
+    }
+}
+
+pub fn levers(
+    keys: Res<Input<KeyCode>>,
+    contacts: Res<Contacts>,
+    mut levers: Query<(Entity, &mut Lever, &mut Switch)>,
+    players: Query<Entity, With<Player>>,
+) {
+    // edge-triggered off our own state rather than `just_pressed`, which a
+    // fixed timestep can see zero or several times in one frame
+    let pressed = keys.pressed(LEVER_KEY);
+
+    for (entity, mut lever, mut switch) in levers.iter_mut() {
+        if pressed
+            && !lever.held
+            && players
+                .iter()
+                .any(|player| contacts.contains(entity, player))
+        {
+            switch.on = !switch.on;
+        }
+
+        lever.held = pressed;
+    }
+}
+
+fn is_powered(powered: &Powered, switches: &Query<&Switch>) -> bool {
+    powered
+        .sources
+        .iter()
+        .any(|source| switches.get(*source).map_or(false, |switch| switch.on))
+}
+
+pub fn power_escalators(
+    mut escalators: Query<(&Powered, &PoweredState, &mut Escalator)>,
+    switches: Query<&Switch>,
+) {
+    for (powered, powered_state, mut escalator) in escalators.iter_mut() {
+        escalator.state = if is_powered(powered, &switches) {
+            powered_state.0
+        } else {
+            EscalatorState::Stopped
+        };
+    }
+}
+
+pub fn power_conveyors(
+    mut conveyors: Query<(&Powered, &PoweredState, &mut Conveyor)>,
+    switches: Query<&Switch>,
+) {
+    for (powered, powered_state, mut conveyor) in conveyors.iter_mut() {
+        conveyor.state = if is_powered(powered, &switches) {
+            powered_state.0
+        } else {
+            EscalatorState::Stopped
+        };
+    }
+}
+
+pub fn power_doors(
+    mut commands: Commands,
+    mut doors: Query<(Entity, &Powered, &mut Door, &mut Visible)>,
+    switches: Query<&Switch>,
+) {
+    for (entity, powered, mut door, mut visible) in doors.iter_mut() {
+        let open = is_powered(powered, &switches);
+        if open == door.open {
+            continue;
+        }
+
+        door.open = open;
+        visible.is_visible = !open;
+
+        if open {
+            commands.entity(entity).remove::<ConvexPolygon>();
+        } else {
+            commands.entity(entity).insert(door.shape.clone());
+        }
+    }
+}
@@ -0,0 +1,87 @@
+use bevy::{app::Events, ecs::system::CommandQueue, prelude::*};
+
+use crate::{
+    add_physics_systems, hold_exactly, Broadphase, CollisionEvent, Contacts, GoalReached, History,
+    Replay, ReplayMode, Solver,
+};
+
+/// A headless world that steps the physics pipeline one tick at a time.
+///
+/// Nothing is rendered and no wall-clock time is involved, so a given
+/// sequence of `spawn` and `tick` calls always ends in the same state.
+pub struct Simulation {
+    pub world: World,
+    stage: SystemStage,
+}
+
+impl Default for Simulation {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Simulation {
+    pub fn new() -> Self {
+        let mut world = World::default();
+        world.insert_resource(Input::<KeyCode>::default());
+        world.insert_resource(Broadphase::default());
+        world.insert_resource(Contacts::default());
+        world.insert_resource(Events::<CollisionEvent>::default());
+        world.insert_resource(GoalReached::default());
+        world.insert_resource(History::default());
+        world.insert_resource(ReplayMode::default());
+        world.insert_resource(Solver::default());
+
+        let mut stage = SystemStage::single_threaded();
+        add_physics_systems(&mut stage);
+
+        Simulation { world, stage }
+    }
+
+    /// Runs `f` against a `Commands` for this world and applies the result,
+    /// so the `spawn_*` helpers can be used directly.
+    pub fn spawn<R>(&mut self, f: impl FnOnce(&mut Commands) -> R) -> R {
+        let mut queue = CommandQueue::default();
+        let result = {
+            let mut commands = Commands::new(&mut queue, &self.world);
+            f(&mut commands)
+        };
+        queue.apply(&mut self.world);
+        result
+    }
+
+    /// Steps a single tick with exactly `keys` held down.
+    pub fn tick(&mut self, keys: &[KeyCode]) {
+        {
+            let mut input = self
+                .world
+                .get_resource_mut::<Input<KeyCode>>()
+                .expect("Input<KeyCode> resource");
+            input.update();
+            hold_exactly(&mut input, keys);
+        }
+
+        self.stage.run(&mut self.world);
+    }
+
+    /// Steps `ticks` ticks with the same `keys` held down throughout.
+    pub fn run(&mut self, ticks: usize, keys: &[KeyCode]) {
+        for _ in 0..ticks {
+            self.tick(keys);
+        }
+    }
+
+    /// Steps through every tick of `replay`. The replay's level is not loaded.
+    pub fn play(&mut self, replay: &Replay) {
+        for held in replay.inputs.iter() {
+            self.run(held.ticks, &held.keys);
+        }
+    }
+
+    pub fn transform(&self, entity: Entity) -> Transform {
+        *self
+            .world
+            .get::<Transform>(entity)
+            .expect("Simulation transform lookup")
+    }
+}
@@ -0,0 +1,60 @@
+use bevy::prelude::*;
+
+use crate::{
+    process_collisions, refresh_contacts, reset_velocity, update_position, CollisionLabel,
+    PreCollisionLabel,
+};
+
+/// Collision passes per tick when nothing else is configured.
+pub const SOLVER_ITERATIONS: usize = 8;
+
+/// Pushes overlapping bodies apart after integration.
+///
+/// One pass only resolves each contact against where its neighbours were,
+/// so a crate squeezed between a step and the crate above it ends the pass
+/// still overlapping one of them. Contacts are found once, and each further
+/// pass resolves what is left of them after the corrections so far, so a
+/// stack settles after about as many passes as it is tall.
+pub struct Solver {
+    pub iterations: usize,
+    contacts: SystemStage,
+    pass: SystemStage,
+}
+
+impl Solver {
+    pub fn new(iterations: usize) -> Self {
+        let mut contacts = SystemStage::single_threaded();
+        contacts.add_system(refresh_contacts.system());
+
+        let mut pass = SystemStage::single_threaded();
+        pass.add_system(reset_velocity.system().label(PreCollisionLabel))
+            .add_system(
+                process_collisions
+                    .system()
+                    .after(PreCollisionLabel)
+                    .label(CollisionLabel),
+            )
+            .add_system(update_position.system().after(CollisionLabel));
+
+        Solver {
+            iterations,
+            contacts,
+            pass,
+        }
+    }
+}
+
+impl Default for Solver {
+    fn default() -> Self {
+        Solver::new(SOLVER_ITERATIONS)
+    }
+}
+
+pub fn solve_collisions(world: &mut World) {
+    world.resource_scope(|world, mut solver: Mut<Solver>| {
+        solver.contacts.run(world);
+        for _ in 0..solver.iterations {
+            solver.pass.run(world);
+        }
+    });
+}
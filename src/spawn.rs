@@ -0,0 +1,518 @@
+use bevy::prelude::*;
+use nalgebra::Point2;
+use parry2d::shape::ConvexPolygon;
+
+use crate::{
+    AngularVelocity, Climbing, CollisionLayers, Conveyor, Crate, Door, Escalator, Exit, Facing,
+    Grabbing, Ground, IgnoreContacts, Jump, Ladder, Lever, Mass, Momentum, OneWay, Path,
+    PathProgress, Player, PoweredState, PressurePlate, Step, Support, Switch, TargetPad, Track,
+    Velocity,
+};
+
+pub fn spawn_escalator(
+    commands: &mut Commands,
+    texture: Handle<TextureAtlas>,
+    transform: Transform,
+    escalator: Escalator,
+) -> Entity {
+    let length = escalator.length;
+    let facing = escalator.facing;
+    let powered_state = PoweredState::new(escalator.state);
+
+    let hull: Vec<_> = [
+        Vec2::new(-length / 2.0, length / 2.0 - 10.0),
+        Vec2::new(length / 2.0, -length / 2.0),
+        Vec2::new(-length / 2.0, -length / 2.0),
+    ]
+    .iter()
+    .map(|point| {
+        let point = facing.orient(*point);
+        Point2::new(point.x, point.y)
+    })
+    .collect();
+
+    commands
+        .spawn()
+        .insert_bundle(SpriteSheetBundle {
+            sprite: TextureAtlasSprite {
+                color: Color::rgba(1.0, 1.0, 1.0, 0.5),
+                flip_x: facing == Facing::Right,
+                ..TextureAtlasSprite::default()
+            },
+            visible: Visible {
+                is_visible: true,
+                is_transparent: true,
+            },
+            texture_atlas: texture.clone_weak(),
+            transform,
+            ..Default::default()
+        })
+        .insert(escalator)
+        .insert(powered_state)
+        .insert(CollisionLayers::ESCALATOR)
+        .insert(ConvexPolygon::from_convex_hull(&hull).expect("polygon"))
+        .id()
+}
+
+pub fn spawn_ladder(
+    commands: &mut Commands,
+    material: Handle<ColorMaterial>,
+    transform: Transform,
+    size: Vec2,
+) -> Entity {
+    commands
+        .spawn_bundle(SpriteBundle {
+            material,
+            transform,
+            sprite: Sprite::new(size),
+            ..Default::default()
+        })
+        .insert(Ladder)
+        .insert(OneWay)
+        .insert(CollisionLayers::LADDER)
+        .insert(
+            ConvexPolygon::from_convex_hull(&[
+                Point2::new(-size.x / 2.0, size.y / 2.0),
+                Point2::new(size.x / 2.0, size.y / 2.0),
+                Point2::new(size.x / 2.0, -size.y / 2.0),
+                Point2::new(-size.x / 2.0, -size.y / 2.0),
+            ])
+            .expect("poly"),
+        )
+        .id()
+}
+
+pub fn spawn_step(
+    commands: &mut Commands,
+    material: Handle<ColorMaterial>,
+    escalator: Entity,
+    transform: Transform,
+    length: f32,
+    track_position: f32,
+    track_length: f32,
+) -> Entity {
+    commands
+        .spawn()
+        .insert_bundle(SpriteBundle {
+            material,
+            transform,
+            sprite: Sprite::new(Vec2::splat(length)),
+            ..Default::default()
+        })
+        .insert(Step { escalator, length })
+        .insert(CollisionLayers::STEP)
+        .insert(IgnoreContacts(vec![escalator]))
+        .insert(Velocity(Vec2::ZERO))
+        .insert(Track {
+            length: track_length,
+            position: track_position,
+        })
+        .insert(
+            ConvexPolygon::from_convex_hull(&[
+                Point2::new(-length / 2.0, length / 2.0),
+                Point2::new(length / 2.0, length / 2.0),
+                Point2::new(length / 2.0, -length / 2.0),
+                Point2::new(-length / 2.0, -length / 2.0),
+            ])
+            .expect("poly"),
+        )
+        .id()
+}
+
+pub fn spawn_ground(
+    commands: &mut Commands,
+    material: Handle<ColorMaterial>,
+    ground_box: Vec2,
+    transform: Transform,
+) -> Entity {
+    commands
+        .spawn()
+        .insert_bundle(SpriteBundle {
+            sprite: Sprite::new(ground_box),
+            material,
+            transform,
+            ..Default::default()
+        })
+        .insert(Ground)
+        .insert(CollisionLayers::GROUND)
+        .insert(
+            ConvexPolygon::from_convex_hull(&[
+                Point2::new(-ground_box.x / 2.0, ground_box.y / 2.0),
+                Point2::new(ground_box.x / 2.0, ground_box.y / 2.0),
+                Point2::new(ground_box.x / 2.0, -ground_box.y / 2.0),
+                Point2::new(-ground_box.x / 2.0, -ground_box.y / 2.0),
+            ])
+            .expect("polygon"),
+        )
+        .id()
+}
+
+/// A slab of ground that bodies jump or climb up through and land on top of.
+pub fn spawn_platform(
+    commands: &mut Commands,
+    material: Handle<ColorMaterial>,
+    size: Vec2,
+    transform: Transform,
+) -> Entity {
+    let platform = spawn_ground(commands, material, size, transform);
+    commands.entity(platform).insert(OneWay);
+    platform
+}
+
+pub fn spawn_conveyor(
+    commands: &mut Commands,
+    material: Handle<ColorMaterial>,
+    size: Vec2,
+    transform: Transform,
+    conveyor: Conveyor,
+) -> Entity {
+    let powered_state = PoweredState::new(conveyor.state);
+    let belt = spawn_ground(commands, material, size, transform);
+    commands.entity(belt).insert(conveyor).insert(powered_state);
+    belt
+}
+
+/// A box of ground that follows `path`, starting from its first waypoint.
+///
+/// Like steps, it's driven straight to each point along the way and nothing
+/// pushes it off course; riders are carried along.
+pub fn spawn_moving_platform(
+    commands: &mut Commands,
+    material: Handle<ColorMaterial>,
+    size: Vec2,
+    path: Path,
+) -> Entity {
+    let start = path.point_at(0.0);
+    let platform = spawn_ground(
+        commands,
+        material,
+        size,
+        Transform::from_translation(start.extend(0.0)),
+    );
+    commands
+        .entity(platform)
+        .insert(path)
+        .insert(PathProgress::default())
+        .insert(Velocity(Vec2::ZERO));
+    platform
+}
+
+/// A right-angled wedge of ground filling `size`, rising towards `facing`.
+///
+/// There's no art for ramps yet, so the sprite is hidden and only the debug
+/// lines show them.
+pub fn spawn_ramp(
+    commands: &mut Commands,
+    material: Handle<ColorMaterial>,
+    size: Vec2,
+    facing: Facing,
+    transform: Transform,
+) -> Entity {
+    let hull: Vec<_> = [
+        Vec2::new(-size.x / 2.0, size.y / 2.0),
+        Vec2::new(size.x / 2.0, -size.y / 2.0),
+        Vec2::new(-size.x / 2.0, -size.y / 2.0),
+    ]
+    .iter()
+    .map(|point| {
+        let point = facing.orient(*point);
+        Point2::new(point.x, point.y)
+    })
+    .collect();
+
+    commands
+        .spawn()
+        .insert_bundle(SpriteBundle {
+            sprite: Sprite::new(size),
+            material,
+            transform,
+            visible: Visible {
+                is_visible: false,
+                is_transparent: true,
+            },
+            ..Default::default()
+        })
+        .insert(Ground)
+        .insert(CollisionLayers::GROUND)
+        .insert(ConvexPolygon::from_convex_hull(&hull).expect("polygon"))
+        .id()
+}
+
+pub fn spawn_player(
+    commands: &mut Commands,
+    material: Handle<ColorMaterial>,
+    size: Vec2,
+    transform: Transform,
+) -> Entity {
+    commands
+        .spawn()
+        .insert_bundle(SpriteBundle {
+            sprite: Sprite::new(size),
+            transform,
+            material,
+            ..SpriteBundle::default()
+        })
+        .insert(Player)
+        .insert(Jump::default())
+        .insert(Climbing::default())
+        .insert(Grabbing::default())
+        .insert(CollisionLayers::PLAYER)
+        .insert(Mass::default())
+        .insert(Support::default())
+        .insert(Momentum::default())
+        .insert(Velocity(Vec2::ZERO))
+        .insert(
+            ConvexPolygon::from_convex_hull(&[
+                Point2::new(-size.x / 2.0, size.y / 2.0),
+                Point2::new(size.x / 2.0, size.y / 2.0),
+                Point2::new(size.x / 2.0, -size.y / 2.0),
+                Point2::new(-size.x / 2.0, -size.y / 2.0),
+            ])
+            .expect("poly"),
+        )
+        .id()
+}
+
+pub fn spawn_crate(
+    commands: &mut Commands,
+    material: Handle<ColorMaterial>,
+    size: Vec2,
+    transform: Transform,
+) -> Entity {
+    commands
+        .spawn_bundle(SpriteBundle {
+            transform,
+            sprite: Sprite::new(size),
+            material,
+            ..Default::default()
+        })
+        .insert(Crate {})
+        .insert(CollisionLayers::CRATE)
+        .insert(Mass::default())
+        .insert(Support::default())
+        .insert(Momentum::default())
+        .insert(AngularVelocity::default())
+        .insert(Velocity(Vec2::ZERO))
+        .insert(
+            ConvexPolygon::from_convex_hull(&[
+                Point2::new(-size.x / 2.0, size.y / 2.0),
+                Point2::new(size.x / 2.0, size.y / 2.0),
+                Point2::new(size.x / 2.0, -size.y / 2.0),
+                Point2::new(-size.x / 2.0, -size.y / 2.0),
+            ])
+            .expect("poly"),
+        )
+        .id()
+}
+
+pub fn spawn_pressure_plate(
+    commands: &mut Commands,
+    material: Handle<ColorMaterial>,
+    size: Vec2,
+    transform: Transform,
+) -> Entity {
+    commands
+        .spawn_bundle(SpriteBundle {
+            transform,
+            sprite: Sprite::new(size),
+            material,
+            ..Default::default()
+        })
+        .insert(PressurePlate)
+        .insert(Switch::default())
+        .insert(CollisionLayers::SENSOR)
+        .insert(
+            ConvexPolygon::from_convex_hull(&[
+                Point2::new(-size.x / 2.0, size.y / 2.0),
+                Point2::new(size.x / 2.0, size.y / 2.0),
+                Point2::new(size.x / 2.0, -size.y / 2.0),
+                Point2::new(-size.x / 2.0, -size.y / 2.0),
+            ])
+            .expect("poly"),
+        )
+        .id()
+}
+
+pub fn spawn_lever(
+    commands: &mut Commands,
+    material: Handle<ColorMaterial>,
+    size: Vec2,
+    transform: Transform,
+) -> Entity {
+    commands
+        .spawn_bundle(SpriteBundle {
+            transform,
+            sprite: Sprite::new(size),
+            material,
+            ..Default::default()
+        })
+        .insert(Lever::default())
+        .insert(Switch::default())
+        .insert(CollisionLayers::SENSOR)
+        .insert(
+            ConvexPolygon::from_convex_hull(&[
+                Point2::new(-size.x / 2.0, size.y / 2.0),
+                Point2::new(size.x / 2.0, size.y / 2.0),
+                Point2::new(size.x / 2.0, -size.y / 2.0),
+                Point2::new(-size.x / 2.0, -size.y / 2.0),
+            ])
+            .expect("poly"),
+        )
+        .id()
+}
+
+pub fn spawn_door(
+    commands: &mut Commands,
+    material: Handle<ColorMaterial>,
+    size: Vec2,
+    transform: Transform,
+) -> Entity {
+    let shape = ConvexPolygon::from_convex_hull(&[
+        Point2::new(-size.x / 2.0, size.y / 2.0),
+        Point2::new(size.x / 2.0, size.y / 2.0),
+        Point2::new(size.x / 2.0, -size.y / 2.0),
+        Point2::new(-size.x / 2.0, -size.y / 2.0),
+    ])
+    .expect("poly");
+
+    commands
+        .spawn_bundle(SpriteBundle {
+            transform,
+            sprite: Sprite::new(size),
+            material,
+            ..Default::default()
+        })
+        .insert(Door {
+            open: false,
+            shape: shape.clone(),
+        })
+        .insert(CollisionLayers::GROUND)
+        .insert(shape)
+        .id()
+}
+
+pub fn spawn_exit(
+    commands: &mut Commands,
+    material: Handle<ColorMaterial>,
+    size: Vec2,
+    transform: Transform,
+) -> Entity {
+    commands
+        .spawn_bundle(SpriteBundle {
+            transform,
+            sprite: Sprite::new(size),
+            material,
+            ..Default::default()
+        })
+        .insert(Exit)
+        .insert(CollisionLayers::SENSOR)
+        .insert(
+            ConvexPolygon::from_convex_hull(&[
+                Point2::new(-size.x / 2.0, size.y / 2.0),
+                Point2::new(size.x / 2.0, size.y / 2.0),
+                Point2::new(size.x / 2.0, -size.y / 2.0),
+                Point2::new(-size.x / 2.0, -size.y / 2.0),
+            ])
+            .expect("poly"),
+        )
+        .id()
+}
+
+pub fn spawn_target_pad(
+    commands: &mut Commands,
+    material: Handle<ColorMaterial>,
+    size: Vec2,
+    transform: Transform,
+) -> Entity {
+    commands
+        .spawn_bundle(SpriteBundle {
+            transform,
+            sprite: Sprite::new(size),
+            material,
+            ..Default::default()
+        })
+        .insert(TargetPad)
+        .insert(Switch::default())
+        .insert(CollisionLayers::SENSOR)
+        .insert(
+            ConvexPolygon::from_convex_hull(&[
+                Point2::new(-size.x / 2.0, size.y / 2.0),
+                Point2::new(size.x / 2.0, size.y / 2.0),
+                Point2::new(size.x / 2.0, -size.y / 2.0),
+                Point2::new(-size.x / 2.0, -size.y / 2.0),
+            ])
+            .expect("poly"),
+        )
+        .id()
+}
+
+pub fn steps(
+    escalator_transform: Transform,
+    escalator_length: f32,
+    step_length: f32,
+    facing: Facing,
+) -> Vec<(Transform, f32, f32)> {
+    let place = |offset: Vec2| Transform {
+        translation: escalator_transform.translation
+            + escalator_transform.rotation * facing.orient(offset).extend(0.0),
+        rotation: escalator_transform.rotation,
+        ..Default::default()
+    };
+
+    let mut result = vec![];
+    let n = (escalator_length / step_length) as i32;
+
+    let track_length = (2.0 * (n as f32 - 1.0) + 2.0) * step_length;
+    let mut track_position = 0.0;
+
+    // A
+    result.push((
+        place(Vec2::new(
+            -escalator_length / 2.0 + step_length / 2.0,
+            escalator_length / 2.0 - step_length / 2.0,
+        )),
+        track_position,
+        track_length,
+    ));
+
+    track_position += step_length;
+
+    // B
+
+    for index in 0..n - 2 {
+        result.push((
+            place(Vec2::new(
+                -escalator_length / 2.0 + step_length / 2.0 + index as f32 * step_length,
+                escalator_length / 2.0 - 3.0 * step_length / 2.0 - index as f32 * step_length,
+            )),
+            track_position,
+            track_length,
+        ));
+        track_position += step_length;
+    }
+
+    // C
+    result.push((
+        place(Vec2::new(
+            escalator_length / 2.0 - 3.0 * step_length / 2.0,
+            -escalator_length / 2.0 + step_length / 2.0,
+        )),
+        track_position,
+        track_length,
+    ));
+    track_position += step_length;
+
+    // D
+    for index in 0..n {
+        result.push((
+            place(Vec2::new(
+                escalator_length / 2.0 - step_length / 2.0 - (index as f32) * step_length,
+                -escalator_length / 2.0 + step_length / 2.0 + (index as f32) * step_length,
+            )),
+            track_position,
+            track_length,
+        ));
+        track_position += step_length;
+    }
+    result
+}
@@ -0,0 +1,142 @@
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+
+use crate::{Contacts, EscalatorState, Facing, PhysicsMaterial, Velocity};
+
+/// The body this one is resting on, if any, found from the start-of-tick
+/// contacts.
+///
+/// A body rests on whatever it touches with the most downward-facing normal,
+/// the same contacts `normal_force` holds it up against. Having one is what
+/// counts as standing on the ground.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Support(pub Option<Entity>);
+
+pub fn update_supports(contacts: Res<Contacts>, mut bodies: Query<(Entity, &mut Support)>) {
+    for (entity, mut support) in bodies.iter_mut() {
+        support.0 = contacts
+            .solid()
+            .filter_map(|(a, b, contact)| {
+                if a == entity {
+                    Some((b, contact.normal1))
+                } else if b == entity {
+                    Some((a, contact.normal2))
+                } else {
+                    None
+                }
+            })
+            .filter(|(_, normal)| normal.y < 0.0)
+            .min_by(|(_, a), (_, b)| a.y.partial_cmp(&b.y).expect("finite normal"))
+            .map(|(other, _)| other);
+    }
+}
+
+/// The velocity `entity` passes on to its riders: its own, plus whatever its
+/// support carries it at along the surface between them.
+fn total_velocity(
+    entity: Entity,
+    contacts: &Contacts,
+    supports: &Query<(Entity, &Support)>,
+    materials: &Query<&PhysicsMaterial>,
+    intrinsic: &HashMap<Entity, Vec2>,
+    carried: &mut HashMap<Entity, Vec2>,
+) -> Vec2 {
+    let own = intrinsic.get(&entity).copied().unwrap_or(Vec2::ZERO);
+    own + carried_velocity(entity, contacts, supports, materials, intrinsic, carried)
+}
+
+fn carried_velocity(
+    entity: Entity,
+    contacts: &Contacts,
+    supports: &Query<(Entity, &Support)>,
+    materials: &Query<&PhysicsMaterial>,
+    intrinsic: &HashMap<Entity, Vec2>,
+    carried: &mut HashMap<Entity, Vec2>,
+) -> Vec2 {
+    if let Some(velocity) = carried.get(&entity) {
+        return *velocity;
+    }
+
+    // settle this entry first, so a cycle of supports can't recurse forever
+    carried.insert(entity, Vec2::ZERO);
+
+    let support = match supports.get(entity) {
+        Ok((_, Support(Some(support)))) => *support,
+        _ => return Vec2::ZERO,
+    };
+    let normal = match contacts.normal(entity, support) {
+        Some(normal) => normal,
+        None => return Vec2::ZERO,
+    };
+
+    let tangent = normal.perp().normalize();
+    let grip = PhysicsMaterial::between(materials, entity, support)
+        .friction
+        .min(1.0);
+    let below = total_velocity(support, contacts, supports, materials, intrinsic, carried);
+    let velocity = grip * below.dot(tangent) * tangent;
+
+    carried.insert(entity, velocity);
+    velocity
+}
+
+/// Ground whose surface moves, carrying whatever rests on it towards `facing`.
+/// Powering one on and off works the same as for escalators.
+pub struct Conveyor {
+    pub facing: Facing,
+    /// Multiplier on `BASE_SPEED_FACTOR`, as for escalators.
+    pub speed: f32,
+    pub state: EscalatorState,
+}
+
+impl Conveyor {
+    /// Velocity of the belt's top surface.
+    pub fn surface_velocity(&self) -> Vec2 {
+        self.facing.orient(-Vec2::X) * self.state.sign() * self.speed
+    }
+}
+
+/*
+Riders move with what they're standing on.
+Every velocity set so far is a body's own, intrinsic motion, and a conveyor's
+is the speed of its belt. On top of that each body inherits its support's
+velocity along the surface between them, scaled by the friction of the
+pair, and that support's velocity already includes what carries it, so a
+player on a crate on a step moves with the step.
+*/
+pub fn carry(
+    contacts: Res<Contacts>,
+    supports: Query<(Entity, &Support)>,
+    materials: Query<&PhysicsMaterial>,
+    conveyors: Query<(Entity, &Conveyor)>,
+    mut velocities: Query<(Entity, &mut Velocity)>,
+) {
+    let intrinsic: HashMap<_, _> = velocities
+        .iter_mut()
+        .map(|(entity, velocity)| (entity, velocity.0))
+        .chain(
+            conveyors
+                .iter()
+                .map(|(entity, conveyor)| (entity, conveyor.surface_velocity())),
+        )
+        .collect();
+
+    let mut carried = HashMap::new();
+    for (entity, _) in supports.iter() {
+        carried_velocity(
+            entity,
+            &contacts,
+            &supports,
+            &materials,
+            &intrinsic,
+            &mut carried,
+        );
+    }
+
+    for (entity, mut velocity) in velocities.iter_mut() {
+        if let Some(carried) = carried.get(&entity) {
+            velocity.0 += *carried;
+        }
+    }
+}
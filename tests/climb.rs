@@ -0,0 +1,103 @@
+use bevy::prelude::*;
+use staircases::*;
+
+fn t(x: f32, y: f32) -> Transform {
+    Transform::from_translation(Vec3::new(x, y, 0.0))
+}
+
+/// A 400 tall ladder standing on the ground, and a player at `(x, y)`.
+fn ladder_and_player(sim: &mut Simulation, x: f32, y: f32) -> Entity {
+    sim.spawn(|commands| {
+        spawn_ground(commands, Handle::default(), Vec2::new(400., 50.), t(0., 0.));
+        spawn_ladder(
+            commands,
+            Handle::default(),
+            t(0., 200.),
+            Vec2::new(50., 400.),
+        );
+        spawn_player(commands, Handle::default(), Vec2::new(50., 100.), t(x, y))
+    })
+}
+
+#[test]
+fn player_stands_on_ladder_top() {
+    let mut sim = Simulation::new();
+    let player = ladder_and_player(&mut sim, 0., 460.);
+
+    sim.run(60, &[]);
+
+    let y = sim.transform(player).translation.y;
+    assert!((y - 450.0).abs() < 1.0, "player at y = {}", y);
+}
+
+#[test]
+fn player_climbs_down_from_ladder_top_and_holds_on() {
+    let mut sim = Simulation::new();
+    let player = ladder_and_player(&mut sim, 0., 460.);
+    sim.run(30, &[]);
+
+    sim.run(60, &[KeyCode::S]);
+    let climbed = sim.transform(player).translation.y;
+    assert!(
+        climbed < 400.0,
+        "player only climbed down to y = {}",
+        climbed
+    );
+
+    sim.run(30, &[]);
+    let y = sim.transform(player).translation.y;
+    assert!(
+        (y - climbed).abs() < 0.5,
+        "player slid from {} to {}",
+        climbed,
+        y
+    );
+
+    sim.run(300, &[KeyCode::S]);
+    let y = sim.transform(player).translation.y;
+    assert!((y - 75.0).abs() < 1.0, "player at y = {}", y);
+}
+
+#[test]
+fn player_dismounts_either_side() {
+    for &(key, side) in [(KeyCode::A, -1.0), (KeyCode::D, 1.0)].iter() {
+        let mut sim = Simulation::new();
+        let player = ladder_and_player(&mut sim, 0., 75.);
+        sim.run(60, &[KeyCode::W]);
+        let climbed = sim.transform(player).translation.y;
+
+        sim.run(30, &[key]);
+
+        assert_eq!(sim.world.get::<Climbing>(player), Some(&Climbing(None)));
+        let translation = sim.transform(player).translation;
+        assert!(
+            translation.x * side > 25.0,
+            "player at x = {}",
+            translation.x
+        );
+        assert!(translation.y < climbed, "player at y = {}", translation.y);
+    }
+}
+
+#[test]
+fn player_snaps_onto_nearby_ladder() {
+    let mut sim = Simulation::new();
+    let player = ladder_and_player(&mut sim, 15., 75.);
+
+    sim.run(30, &[KeyCode::W]);
+
+    let translation = sim.transform(player).translation;
+    assert!(translation.x.abs() < 0.5, "player at x = {}", translation.x);
+    assert!(translation.y > 100.0, "player at y = {}", translation.y);
+}
+
+#[test]
+fn player_out_of_reach_of_ladder_stays_put() {
+    let mut sim = Simulation::new();
+    let player = ladder_and_player(&mut sim, 40., 75.);
+
+    sim.run(30, &[KeyCode::W]);
+
+    let y = sim.transform(player).translation.y;
+    assert!((y - 75.0).abs() < 1.0, "player at y = {}", y);
+}
@@ -0,0 +1,137 @@
+use bevy::prelude::*;
+use staircases::*;
+
+fn t(x: f32, y: f32) -> Transform {
+    Transform::from_translation(Vec3::new(x, y, 0.0))
+}
+
+/// A player on the ground with a crate just to their right.
+fn player_beside_crate(sim: &mut Simulation) -> (Entity, Entity) {
+    sim.spawn(|commands| {
+        spawn_ground(commands, Handle::default(), Vec2::new(400., 50.), t(0., 0.));
+        (
+            spawn_player(
+                commands,
+                Handle::default(),
+                Vec2::new(50., 100.),
+                t(0., 75.),
+            ),
+            spawn_crate(
+                commands,
+                Handle::default(),
+                Vec2::new(50., 50.),
+                t(50., 50.),
+            ),
+        )
+    })
+}
+
+#[test]
+fn player_pulls_crate_at_reduced_speed() {
+    let mut sim = Simulation::new();
+    let (player, crate_entity) = player_beside_crate(&mut sim);
+
+    sim.run(30, &[GRAB_KEY, KeyCode::A]);
+
+    let player_x = sim.transform(player).translation.x;
+    let crate_x = sim.transform(crate_entity).translation.x;
+    assert!(
+        (player_x + 30. * GRAB_SPEED * DELTA).abs() < 1.0,
+        "player at x = {}",
+        player_x
+    );
+    assert!(
+        (crate_x - player_x - 50.).abs() < 1.0,
+        "crate at x = {}, player at x = {}",
+        crate_x,
+        player_x
+    );
+}
+
+#[test]
+fn player_pushes_crate_at_reduced_speed() {
+    let mut sim = Simulation::new();
+    let (player, crate_entity) = player_beside_crate(&mut sim);
+
+    sim.run(30, &[GRAB_KEY, KeyCode::D]);
+
+    let player_x = sim.transform(player).translation.x;
+    let crate_x = sim.transform(crate_entity).translation.x;
+    assert!(
+        (crate_x - 50. - 30. * GRAB_SPEED * DELTA).abs() < 1.0,
+        "crate at x = {}",
+        crate_x
+    );
+    assert!(
+        (crate_x - player_x - 50.).abs() < 1.0,
+        "crate at x = {}, player at x = {}",
+        crate_x,
+        player_x
+    );
+}
+
+#[test]
+fn letting_go_leaves_crate_behind() {
+    let mut sim = Simulation::new();
+    let (player, crate_entity) = player_beside_crate(&mut sim);
+
+    sim.run(10, &[GRAB_KEY, KeyCode::A]);
+    let before = sim.transform(crate_entity).translation.x;
+
+    sim.run(30, &[KeyCode::A]);
+
+    let x = sim.transform(crate_entity).translation.x;
+    assert!(
+        (x - before).abs() < 1e-3,
+        "crate moved from {} to {}",
+        before,
+        x
+    );
+    assert_eq!(sim.world.get::<Grabbing>(player), Some(&Grabbing(None)));
+}
+
+#[test]
+fn crate_carried_out_of_reach_is_let_go() {
+    let mut sim = Simulation::new();
+
+    let (player, crate_entity) = sim.spawn(|commands| {
+        spawn_ground(commands, Handle::default(), Vec2::new(400., 50.), t(0., 0.));
+        spawn_conveyor(
+            commands,
+            Handle::default(),
+            Vec2::new(200., 10.),
+            t(100., 30.),
+            Conveyor {
+                facing: Facing::Right,
+                speed: 1.0,
+                state: EscalatorState::Running,
+            },
+        );
+        (
+            spawn_player(
+                commands,
+                Handle::default(),
+                Vec2::new(50., 100.),
+                t(-30., 75.),
+            ),
+            spawn_crate(
+                commands,
+                Handle::default(),
+                Vec2::new(50., 50.),
+                t(20., 60.),
+            ),
+        )
+    });
+
+    sim.tick(&[GRAB_KEY]);
+    assert_eq!(
+        sim.world.get::<Grabbing>(player),
+        Some(&Grabbing(Some(crate_entity)))
+    );
+
+    sim.run(30, &[GRAB_KEY]);
+
+    let x = sim.transform(crate_entity).translation.x;
+    assert!(x > 40.0, "crate at x = {}", x);
+    assert_eq!(sim.world.get::<Grabbing>(player), Some(&Grabbing(None)));
+}
@@ -0,0 +1,99 @@
+use bevy::prelude::*;
+use staircases::*;
+
+fn t(x: f32, y: f32) -> Transform {
+    Transform::from_translation(Vec3::new(x, y, 0.0))
+}
+
+fn momentum(sim: &Simulation, entity: Entity) -> Vec2 {
+    sim.world.get::<Momentum>(entity).expect("momentum").0
+}
+
+fn standing_player(sim: &mut Simulation, y: f32) -> Entity {
+    sim.spawn(|commands| {
+        spawn_ground(commands, Handle::default(), Vec2::new(200., 50.), t(0., 0.));
+        spawn_player(commands, Handle::default(), Vec2::new(50., 100.), t(0., y))
+    })
+}
+
+#[test]
+fn falling_speeds_up_to_terminal_velocity() {
+    let mut sim = Simulation::new();
+
+    let crate_entity = sim
+        .spawn(|commands| spawn_crate(commands, Handle::default(), Vec2::new(50., 50.), t(0., 0.)));
+
+    sim.tick(&[]);
+    let first = -sim.transform(crate_entity).translation.y;
+
+    sim.run(10, &[]);
+    let before = sim.transform(crate_entity).translation.y;
+    sim.tick(&[]);
+    let later = before - sim.transform(crate_entity).translation.y;
+    assert!(later > first, "fell {} then {}", first, later);
+
+    sim.run(100, &[]);
+    assert_eq!(momentum(&sim, crate_entity).y, -TERMINAL_VELOCITY);
+}
+
+#[test]
+fn holding_jump_jumps_once() {
+    let mut sim = Simulation::new();
+    let player = standing_player(&mut sim, 75.);
+
+    sim.run(2, &[]);
+    sim.tick(&[JUMP_KEY]);
+    assert_eq!(momentum(&sim, player).y, JUMP_SPEED);
+
+    let mut peak = 0.0_f32;
+    for _ in 0..120 {
+        sim.tick(&[JUMP_KEY]);
+        peak = peak.max(sim.transform(player).translation.y);
+    }
+
+    assert!(peak > 125.0, "peaked at y = {}", peak);
+    let y = sim.transform(player).translation.y;
+    assert!((y - 75.0).abs() < 1.0, "player at y = {}", y);
+}
+
+#[test]
+fn jump_just_after_walking_off_edge() {
+    for &(late, jumps) in [(COYOTE_TICKS - 2, true), (COYOTE_TICKS + 2, false)].iter() {
+        let mut sim = Simulation::new();
+        let player = standing_player(&mut sim, 75.);
+
+        // supports are only found once a tick has run
+        sim.tick(&[KeyCode::D]);
+        while sim.world.get::<Support>(player).unwrap().0.is_some() {
+            sim.tick(&[KeyCode::D]);
+        }
+        sim.run(late as usize, &[KeyCode::D]);
+        sim.tick(&[KeyCode::D, JUMP_KEY]);
+
+        assert_eq!(
+            momentum(&sim, player).y > 0.0,
+            jumps,
+            "jump {} ticks after leaving the edge",
+            late
+        );
+    }
+}
+
+#[test]
+fn jump_pressed_just_before_landing_is_remembered() {
+    let mut sim = Simulation::new();
+    let player = standing_player(&mut sim, 100.);
+
+    while sim.transform(player).translation.y - 75.0 > 3.0 {
+        sim.tick(&[]);
+    }
+    sim.tick(&[JUMP_KEY]);
+    assert_eq!(sim.world.get::<Support>(player).unwrap().0, None);
+
+    let mut peak = 0.0_f32;
+    for _ in 0..20 {
+        sim.tick(&[]);
+        peak = peak.max(sim.transform(player).translation.y);
+    }
+    assert!(peak > 100.0, "peaked at y = {}", peak);
+}
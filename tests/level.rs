@@ -0,0 +1,159 @@
+use staircases::*;
+
+#[test]
+fn first_level_loads_and_spawns() {
+    let level = Level::from_ron(include_str!("../assets/levels/01.ron")).expect("level");
+
+    let mut sim = Simulation::new();
+    sim.spawn(|commands| spawn_level(commands, &level, &LevelMaterials::default()))
+        .expect("level wires");
+
+    let world = &mut sim.world;
+    assert_eq!(world.query::<&Player>().iter(world).count(), 1);
+    assert_eq!(world.query::<&Crate>().iter(world).count(), 2);
+    assert_eq!(world.query::<&Ladder>().iter(world).count(), 1);
+    assert_eq!(world.query::<&Escalator>().iter(world).count(), 1);
+    assert_eq!(world.query::<&Step>().iter(world).count(), 8);
+    assert_eq!(world.query::<&Exit>().iter(world).count(), 1);
+    // every level entity, plus the escalator's steps
+    assert_eq!(
+        world.query::<&InLevel>().iter(world).count(),
+        level.entities.len() + 8
+    );
+}
+
+#[test]
+fn escalator_steps_must_fit_on_it() {
+    for step_size in [0.0, -50.0, 250.0].iter() {
+        let source = format!(
+            "(entities: [Escalator(position: (0.0, 0.0), length: 200.0, step_size: {:?})])",
+            step_size
+        );
+
+        assert!(
+            matches!(
+                Level::from_ron(&source),
+                Err(LevelError::BadEscalator { .. })
+            ),
+            "step size {}",
+            step_size
+        );
+    }
+}
+
+#[test]
+fn escalator_speed_must_be_positive() {
+    let source =
+        "(entities: [Escalator(position: (0.0, 0.0), length: 200.0, step_size: 50.0, speed: 0.0)])";
+
+    assert!(matches!(
+        Level::from_ron(source),
+        Err(LevelError::BadSpeed(_))
+    ));
+}
+
+#[test]
+fn conveyor_speed_must_be_positive() {
+    let source = "(entities: [Conveyor(position: (0.0, 0.0), size: (200.0, 50.0), speed: -1.0)])";
+
+    assert!(matches!(
+        Level::from_ron(source),
+        Err(LevelError::BadSpeed(_))
+    ));
+}
+
+#[test]
+fn second_level_wires_switches_to_targets() {
+    let level = Level::from_ron(include_str!("../assets/levels/02.ron")).expect("level");
+
+    let mut sim = Simulation::new();
+    sim.spawn(|commands| spawn_level(commands, &level, &LevelMaterials::default()))
+        .expect("level wires");
+
+    let world = &mut sim.world;
+    assert_eq!(world.query::<&PressurePlate>().iter(world).count(), 1);
+    assert_eq!(world.query::<&Lever>().iter(world).count(), 1);
+    assert_eq!(
+        world.query::<(&Escalator, &Powered)>().iter(world).count(),
+        1
+    );
+    assert_eq!(world.query::<(&Door, &Powered)>().iter(world).count(), 1);
+}
+
+fn spawn_wired(wire: &str) -> (Simulation, Result<(), LevelError>) {
+    let level = Level::from_ron(&format!(
+        "(entities: [\
+            Lever(position: (0.0, 0.0), size: (20.0, 20.0), name: Some(\"lever\")), \
+            Door(position: (100.0, 0.0), size: (20.0, 100.0), name: Some(\"door\")), \
+         ], wires: [{}])",
+        wire
+    ))
+    .expect("level");
+
+    let mut sim = Simulation::new();
+    let result = sim.spawn(|commands| spawn_level(commands, &level, &LevelMaterials::default()));
+    (sim, result)
+}
+
+#[test]
+fn misspelled_wire_spawns_nothing() {
+    let (mut sim, result) = spawn_wired("(from: \"lever\", to: \"dor\")");
+
+    assert!(matches!(result, Err(LevelError::UnknownWire(name)) if name == "dor"));
+    let world = &mut sim.world;
+    assert_eq!(world.query::<&Lever>().iter(world).count(), 0);
+}
+
+#[test]
+fn wires_run_from_switches_to_targets() {
+    let (_, result) = spawn_wired("(from: \"door\", to: \"lever\")");
+
+    assert!(matches!(result, Err(LevelError::BadWire { .. })));
+}
+
+#[test]
+fn switches_can_power_conveyors() {
+    let level = Level::from_ron(
+        "(entities: [\
+            Lever(position: (0.0, 0.0), size: (20.0, 20.0), name: Some(\"lever\")), \
+            Conveyor(position: (0.0, -50.0), size: (200.0, 50.0), name: Some(\"belt\")), \
+         ], wires: [(from: \"lever\", to: \"belt\")])",
+    )
+    .expect("level");
+
+    let mut sim = Simulation::new();
+    sim.spawn(|commands| spawn_level(commands, &level, &LevelMaterials::default()))
+        .expect("level wires");
+
+    let world = &mut sim.world;
+    assert_eq!(
+        world.query::<(&Conveyor, &Powered)>().iter(world).count(),
+        1
+    );
+}
+
+#[test]
+fn names_are_unique() {
+    let level = Level::from_ron(
+        "(entities: [\
+            Lever(position: (0.0, 0.0), size: (20.0, 20.0), name: Some(\"door\")), \
+            Door(position: (100.0, 0.0), size: (20.0, 100.0), name: Some(\"door\")), \
+         ])",
+    )
+    .expect("level");
+
+    let mut sim = Simulation::new();
+    let result = sim.spawn(|commands| spawn_level(commands, &level, &LevelMaterials::default()));
+
+    assert!(matches!(result, Err(LevelError::DuplicateName(name)) if name == "door"));
+}
+
+#[test]
+fn crate_mass_must_be_positive() {
+    let source = "(entities: [Crate(position: (0.0, 0.0), size: (50.0, 50.0), mass: Some(0.0))])";
+
+    assert!(matches!(
+        Level::from_ron(source),
+        Err(LevelError::NonPositiveMass(_))
+    ));
+}
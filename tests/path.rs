@@ -0,0 +1,96 @@
+use bevy::prelude::*;
+use staircases::*;
+
+fn t(x: f32, y: f32) -> Transform {
+    Transform::from_translation(Vec3::new(x, y, 0.0))
+}
+
+fn path(mode: PathMode) -> Path {
+    Path {
+        waypoints: vec![
+            Vec2::new(0., 0.),
+            Vec2::new(100., 0.),
+            Vec2::new(100., 100.),
+        ],
+        speed: 1.0,
+        mode,
+    }
+}
+
+#[test]
+fn loops_close_back_to_the_start() {
+    let path = path(PathMode::Loop);
+    let diagonal = 100. * 2f32.sqrt();
+
+    assert!((path.length() - (200. + diagonal)).abs() < 1e-3);
+    assert_eq!(path.point_at(150.), Vec2::new(100., 50.));
+
+    let mut progress = PathProgress {
+        distance: path.length() - 0.5 * DELTA,
+        reversing: false,
+    };
+    path.advance(&mut progress);
+    assert!((progress.distance - 0.5 * DELTA).abs() < 1e-3);
+}
+
+#[test]
+fn ping_pong_turns_around_at_the_ends() {
+    let path = path(PathMode::PingPong);
+    assert_eq!(path.length(), 200.);
+
+    let mut progress = PathProgress {
+        distance: 200. - 0.5 * DELTA,
+        reversing: false,
+    };
+    path.advance(&mut progress);
+    assert!(progress.reversing);
+    assert!((progress.distance - (200. - 0.5 * DELTA)).abs() < 1e-3);
+
+    progress.distance = 0.5 * DELTA;
+    path.advance(&mut progress);
+    assert!(!progress.reversing);
+    assert!((progress.distance - 0.5 * DELTA).abs() < 1e-3);
+}
+
+#[test]
+fn crate_rides_shuttle() {
+    let mut sim = Simulation::new();
+
+    let (platform, crate_entity) = sim.spawn(|commands| {
+        let platform = spawn_moving_platform(
+            commands,
+            Handle::default(),
+            Vec2::new(100., 20.),
+            Path {
+                waypoints: vec![Vec2::new(0., 0.), Vec2::new(200., 0.)],
+                speed: 1.0,
+                mode: PathMode::PingPong,
+            },
+        );
+        let crate_entity =
+            spawn_crate(commands, Handle::default(), Vec2::new(50., 50.), t(0., 35.));
+        (platform, crate_entity)
+    });
+
+    sim.run(60, &[]);
+
+    let platform_x = sim.transform(platform).translation.x;
+    assert!(
+        (platform_x - 60. * DELTA).abs() < 1e-2,
+        "platform at x = {}",
+        platform_x
+    );
+
+    let crate_xform = sim.transform(crate_entity);
+    assert!(
+        (crate_xform.translation.x - platform_x).abs() < 1.0,
+        "crate at x = {}, platform at x = {}",
+        crate_xform.translation.x,
+        platform_x
+    );
+    assert!(
+        (crate_xform.translation.y - 35.).abs() < 1.0,
+        "crate at y = {}",
+        crate_xform.translation.y
+    );
+}
@@ -0,0 +1,846 @@
+use bevy::{app::Events, prelude::*};
+use staircases::*;
+
+fn t(x: f32, y: f32) -> Transform {
+    Transform::from_translation(Vec3::new(x, y, 0.0))
+}
+
+#[test]
+fn crate_comes_to_rest_on_ground() {
+    let mut sim = Simulation::new();
+
+    let crate_entity = sim.spawn(|commands| {
+        spawn_ground(commands, Handle::default(), Vec2::new(200., 50.), t(0., 0.));
+        spawn_crate(commands, Handle::default(), Vec2::new(50., 50.), t(0., 60.))
+    });
+
+    sim.run(120, &[]);
+
+    let y = sim.transform(crate_entity).translation.y;
+    assert!((y - 50.0).abs() < 1.0, "crate at y = {}", y);
+}
+
+#[test]
+fn player_walks_along_ground() {
+    let mut sim = Simulation::new();
+
+    let player = sim.spawn(|commands| {
+        spawn_ground(commands, Handle::default(), Vec2::new(400., 50.), t(0., 0.));
+        spawn_player(
+            commands,
+            Handle::default(),
+            Vec2::new(50., 100.),
+            t(0., 75.),
+        )
+    });
+
+    sim.run(30, &[KeyCode::D]);
+
+    let translation = sim.transform(player).translation;
+    assert!(translation.x > 30.0, "player at x = {}", translation.x);
+    assert!(
+        (translation.y - 75.0).abs() < 1.0,
+        "player at y = {}",
+        translation.y
+    );
+}
+
+// devlog: "player can hang from bottom of ground"
+#[test]
+fn player_does_not_hang_from_bottom_of_ground() {
+    let mut sim = Simulation::new();
+
+    let player = sim.spawn(|commands| {
+        spawn_ground(commands, Handle::default(), Vec2::new(200., 50.), t(0., 0.));
+        spawn_player(
+            commands,
+            Handle::default(),
+            Vec2::new(50., 100.),
+            t(0., -75.),
+        )
+    });
+
+    sim.run(30, &[]);
+
+    let y = sim.transform(player).translation.y;
+    assert!(y < -100.0, "player at y = {}", y);
+}
+
+#[test]
+fn same_input_gives_same_trajectory() {
+    let run = || {
+        let mut sim = Simulation::new();
+        let player = sim.spawn(|commands| {
+            spawn_ground(commands, Handle::default(), Vec2::new(400., 50.), t(0., 0.));
+            spawn_crate(
+                commands,
+                Handle::default(),
+                Vec2::new(50., 50.),
+                t(60., 50.),
+            );
+            spawn_player(
+                commands,
+                Handle::default(),
+                Vec2::new(50., 100.),
+                t(0., 75.),
+            )
+        });
+        sim.run(20, &[KeyCode::D]);
+        sim.run(20, &[KeyCode::A]);
+        sim.transform(player).translation
+    };
+
+    assert_eq!(run(), run());
+}
+
+// devlog: "steps push through ground"
+#[test]
+fn steps_do_not_push_through_ground() {
+    let mut sim = Simulation::new();
+
+    let escalator_xform = t(0., 125.);
+    let steps = sim.spawn(|commands| {
+        spawn_ground(commands, Handle::default(), Vec2::new(400., 50.), t(0., 0.));
+        let escalator = spawn_escalator(
+            commands,
+            Handle::default(),
+            escalator_xform,
+            Escalator {
+                length: 200.,
+                facing: Facing::Left,
+                travel: Travel::Up,
+                speed: 1.0,
+                state: EscalatorState::Running,
+            },
+        );
+
+        steps(escalator_xform, 200., 50., Facing::Left)
+            .into_iter()
+            .map(|(transform, track_position, track_length)| {
+                spawn_step(
+                    commands,
+                    Handle::default(),
+                    escalator,
+                    transform,
+                    50.,
+                    track_position,
+                    track_length,
+                )
+            })
+            .collect::<Vec<_>>()
+    });
+
+    // long enough for every step to go round the bottom of the track
+    for _ in 0..300 {
+        sim.tick(&[]);
+        for step in steps.iter() {
+            let bottom = sim.transform(*step).translation.y - 25.0;
+            assert!(bottom > 24.0, "step bottom at y = {}", bottom);
+        }
+    }
+}
+
+#[test]
+fn right_facing_steps_mirror_left_facing_steps() {
+    let left = steps(t(10., 0.), 200., 50., Facing::Left);
+    let right = steps(t(10., 0.), 200., 50., Facing::Right);
+
+    assert_eq!(left.len(), right.len());
+    for ((left, left_position, _), (right, right_position, _)) in left.iter().zip(right.iter()) {
+        assert_eq!(left_position, right_position);
+        assert!((left.translation.x - 10. + right.translation.x - 10.).abs() < 1e-4);
+        assert_eq!(left.translation.y, right.translation.y);
+    }
+}
+
+#[test]
+fn stopped_escalator_holds_its_steps() {
+    let mut sim = Simulation::new();
+
+    let escalator_xform = t(0., 0.);
+    let steps = sim.spawn(|commands| {
+        let escalator = spawn_escalator(
+            commands,
+            Handle::default(),
+            escalator_xform,
+            Escalator {
+                length: 200.,
+                facing: Facing::Left,
+                travel: Travel::Up,
+                speed: 1.0,
+                state: EscalatorState::Stopped,
+            },
+        );
+
+        steps(escalator_xform, 200., 50., Facing::Left)
+            .into_iter()
+            .map(|(transform, track_position, track_length)| {
+                spawn_step(
+                    commands,
+                    Handle::default(),
+                    escalator,
+                    transform,
+                    50.,
+                    track_position,
+                    track_length,
+                )
+            })
+            .collect::<Vec<_>>()
+    });
+
+    // let the steps settle onto their tracks
+    sim.run(2, &[]);
+    let before: Vec<_> = steps.iter().map(|step| sim.transform(*step)).collect();
+
+    sim.run(30, &[]);
+    for (step, before) in steps.iter().zip(before) {
+        let after = sim.transform(*step);
+        assert!(
+            (after.translation - before.translation).length() < 1e-3,
+            "step moved from {:?} to {:?}",
+            before.translation,
+            after.translation
+        );
+    }
+}
+
+#[test]
+fn broadphase_pairs_only_nearby_bodies() {
+    let mut sim = Simulation::new();
+
+    let (ground, near, far) = sim.spawn(|commands| {
+        let ground = spawn_ground(commands, Handle::default(), Vec2::new(200., 50.), t(0., 0.));
+        let near = spawn_crate(commands, Handle::default(), Vec2::new(50., 50.), t(0., 50.));
+        let far = spawn_crate(
+            commands,
+            Handle::default(),
+            Vec2::new(50., 50.),
+            t(500., 50.),
+        );
+        (ground, near, far)
+    });
+
+    sim.run(1, &[]);
+
+    let pairs = &sim
+        .world
+        .get_resource::<Broadphase>()
+        .expect("broadphase")
+        .pairs;
+    let paired = |a: Entity, b: Entity| pairs.contains(&(a.min(b), a.max(b)));
+    assert!(paired(ground, near));
+    assert!(!paired(ground, far));
+    assert!(!paired(near, far));
+}
+
+#[test]
+fn contact_events_follow_player_walking_off_ground() {
+    let mut sim = Simulation::new();
+
+    let (ground, player) = sim.spawn(|commands| {
+        let ground = spawn_ground(commands, Handle::default(), Vec2::new(200., 50.), t(0., 0.));
+        let player = spawn_player(
+            commands,
+            Handle::default(),
+            Vec2::new(50., 100.),
+            t(0., 75.),
+        );
+        (ground, player)
+    });
+    let pair = (ground.min(player), ground.max(player));
+
+    let mut reader = sim
+        .world
+        .get_resource::<Events<CollisionEvent>>()
+        .expect("collision events")
+        .get_reader();
+
+    let mut seen = vec![];
+    for _ in 0..150 {
+        sim.tick(&[KeyCode::D]);
+
+        let events = sim
+            .world
+            .get_resource::<Events<CollisionEvent>>()
+            .expect("collision events");
+        for event in reader.iter(events) {
+            match *event {
+                CollisionEvent::Started(a, b) if (a, b) == pair => seen.push("started"),
+                CollisionEvent::Persisted(a, b) if (a, b) == pair => seen.push("persisted"),
+                CollisionEvent::Ended(a, b) if (a, b) == pair => seen.push("ended"),
+                _ => {}
+            }
+        }
+    }
+
+    seen.dedup();
+    assert_eq!(seen, vec!["started", "persisted", "ended"]);
+}
+
+#[test]
+fn player_only_barrier_lets_crates_fall_through() {
+    let mut sim = Simulation::new();
+
+    let (crate_entity, player) = sim.spawn(|commands| {
+        let barrier = spawn_ground(commands, Handle::default(), Vec2::new(400., 50.), t(0., 0.));
+        commands.entity(barrier).insert(CollisionLayers {
+            mask: Layer::Player.bit(),
+            ..CollisionLayers::GROUND
+        });
+
+        let crate_entity = spawn_crate(
+            commands,
+            Handle::default(),
+            Vec2::new(50., 50.),
+            t(-100., 60.),
+        );
+        let player = spawn_player(
+            commands,
+            Handle::default(),
+            Vec2::new(50., 100.),
+            t(100., 75.),
+        );
+        (crate_entity, player)
+    });
+
+    sim.run(120, &[]);
+
+    let crate_y = sim.transform(crate_entity).translation.y;
+    assert!(crate_y < -50.0, "crate at y = {}", crate_y);
+    let player_y = sim.transform(player).translation.y;
+    assert!((player_y - 75.0).abs() < 1.0, "player at y = {}", player_y);
+}
+
+fn shove_crate(mass: Mass) -> f32 {
+    let mut sim = Simulation::new();
+
+    let crate_entity = sim.spawn(|commands| {
+        spawn_ground(commands, Handle::default(), Vec2::new(800., 50.), t(0., 0.));
+        spawn_player(
+            commands,
+            Handle::default(),
+            Vec2::new(50., 100.),
+            t(0., 75.),
+        );
+        let crate_entity = spawn_crate(
+            commands,
+            Handle::default(),
+            Vec2::new(50., 50.),
+            t(80., 50.),
+        );
+        commands.entity(crate_entity).insert(mass);
+        crate_entity
+    });
+
+    sim.run(60, &[KeyCode::D]);
+
+    sim.transform(crate_entity).translation.x - 80.0
+}
+
+#[test]
+fn heavy_crate_resists_being_shoved() {
+    let light = shove_crate(Mass(1.0));
+    let heavy = shove_crate(Mass(10.0));
+
+    assert!(light > 0.0, "light crate moved {}", light);
+    assert!(
+        heavy < light,
+        "heavy crate moved {}, light {}",
+        heavy,
+        light
+    );
+}
+
+#[test]
+fn crate_tower_settles_without_sinking_or_jitter() {
+    let mut sim = Simulation::new();
+
+    let crates = sim.spawn(|commands| {
+        spawn_ground(commands, Handle::default(), Vec2::new(200., 50.), t(0., 0.));
+        (0..4)
+            .map(|level| {
+                spawn_crate(
+                    commands,
+                    Handle::default(),
+                    Vec2::new(50., 50.),
+                    t(0., 52. + 52. * level as f32),
+                )
+            })
+            .collect::<Vec<_>>()
+    });
+
+    sim.run(120, &[]);
+    let settled: Vec<_> = crates
+        .iter()
+        .map(|crate_entity| sim.transform(*crate_entity).translation)
+        .collect();
+
+    for (level, position) in settled.iter().enumerate() {
+        let expected = 50. + 50. * level as f32;
+        assert!(
+            (position.y - expected).abs() < 1.0,
+            "crate {} at y = {}",
+            level,
+            position.y
+        );
+    }
+
+    sim.run(30, &[]);
+    for (crate_entity, before) in crates.iter().zip(settled) {
+        let after = sim.transform(*crate_entity).translation;
+        assert!(
+            (after - before).length() < 0.1,
+            "crate moved from {:?} to {:?}",
+            before,
+            after
+        );
+    }
+}
+
+// A running escalator on the ground, run until its steps are on their
+// tracks; returns a step on its way up.
+fn rising_step(sim: &mut Simulation) -> Entity {
+    let escalator_xform = t(0., 125.);
+    let steps = sim.spawn(|commands| {
+        spawn_ground(commands, Handle::default(), Vec2::new(800., 50.), t(0., 0.));
+        let escalator = spawn_escalator(
+            commands,
+            Handle::default(),
+            escalator_xform,
+            Escalator {
+                length: 200.,
+                facing: Facing::Left,
+                travel: Travel::Up,
+                speed: 1.0,
+                state: EscalatorState::Running,
+            },
+        );
+
+        steps(escalator_xform, 200., 50., Facing::Left)
+            .into_iter()
+            .map(|(transform, track_position, track_length)| {
+                spawn_step(
+                    commands,
+                    Handle::default(),
+                    escalator,
+                    transform,
+                    50.,
+                    track_position,
+                    track_length,
+                )
+            })
+            .collect::<Vec<_>>()
+    });
+
+    sim.run(2, &[]);
+    steps[6]
+}
+
+#[test]
+fn crate_tower_rides_running_steps_without_sinking_or_jitter() {
+    let mut sim = Simulation::new();
+
+    let step = rising_step(&mut sim);
+    let base = sim.transform(step).translation;
+    let crates = sim.spawn(|commands| {
+        (1..4)
+            .map(|level| {
+                spawn_crate(
+                    commands,
+                    Handle::default(),
+                    Vec2::new(50., 50.),
+                    t(base.x, base.y + 50. * level as f32),
+                )
+            })
+            .collect::<Vec<_>>()
+    });
+
+    let offsets = |sim: &Simulation| {
+        let step = sim.transform(step).translation;
+        crates
+            .iter()
+            .map(|crate_entity| sim.transform(*crate_entity).translation - step)
+            .collect::<Vec<_>>()
+    };
+
+    sim.run(30, &[]);
+    let mut last = offsets(&sim);
+    for (level, offset) in last.iter().enumerate() {
+        let expected = 50. * (level + 1) as f32;
+        assert!(
+            (offset.y - expected).abs() < 1.0,
+            "crate {} at {} above its step",
+            level,
+            offset.y
+        );
+    }
+
+    let start = sim.transform(step).translation;
+    for _ in 0..30 {
+        sim.tick(&[]);
+        let now = offsets(&sim);
+        // crates can creep along the step, but mustn't bob up and down on it
+        for (before, after) in last.iter().zip(now.iter()) {
+            assert!(
+                (after.y - before.y).abs() < 0.01,
+                "crate moved from {:?} to {:?} relative to its step",
+                before,
+                after
+            );
+        }
+        last = now;
+    }
+    assert!(sim.transform(step).translation.y > start.y + 30.);
+}
+
+#[test]
+fn crates_stacked_on_player_ride_along() {
+    let mut sim = Simulation::new();
+
+    let (ground, player, bottom, top) = sim.spawn(|commands| {
+        let ground = spawn_ground(commands, Handle::default(), Vec2::new(800., 50.), t(0., 0.));
+        let player = spawn_player(
+            commands,
+            Handle::default(),
+            Vec2::new(50., 100.),
+            t(0., 75.),
+        );
+        let bottom = spawn_crate(
+            commands,
+            Handle::default(),
+            Vec2::new(50., 50.),
+            t(0., 150.),
+        );
+        let top = spawn_crate(
+            commands,
+            Handle::default(),
+            Vec2::new(50., 50.),
+            t(0., 200.),
+        );
+        (ground, player, bottom, top)
+    });
+
+    sim.run(30, &[KeyCode::D]);
+
+    let support = |entity| sim.world.get::<Support>(entity).unwrap().0;
+    assert_eq!(support(player), Some(ground));
+    assert_eq!(support(bottom), Some(player));
+    assert_eq!(support(top), Some(bottom));
+
+    let player_x = sim.transform(player).translation.x;
+    assert!(player_x > 30.0, "player at x = {}", player_x);
+    for crate_entity in [bottom, top].iter() {
+        let x = sim.transform(*crate_entity).translation.x;
+        assert!(
+            (x - player_x).abs() < 1.0,
+            "crate at x = {}, player at x = {}",
+            x,
+            player_x
+        );
+    }
+}
+
+#[test]
+fn player_on_crate_rides_a_running_step() {
+    let mut sim = Simulation::new();
+
+    let step = rising_step(&mut sim);
+    let base = sim.transform(step).translation;
+    let player = sim.spawn(|commands| {
+        spawn_crate(
+            commands,
+            Handle::default(),
+            Vec2::new(50., 50.),
+            t(base.x, base.y + 50.),
+        );
+        spawn_player(
+            commands,
+            Handle::default(),
+            Vec2::new(50., 100.),
+            t(base.x, base.y + 125.),
+        )
+    });
+
+    sim.run(10, &[]);
+    let offset =
+        |sim: &Simulation| sim.transform(player).translation - sim.transform(step).translation;
+    let start = offset(&sim);
+
+    sim.run(30, &[]);
+    let end = offset(&sim);
+    assert!(
+        (end - start).length() < 1.0,
+        "player moved from {:?} to {:?} relative to the step",
+        start,
+        end
+    );
+}
+
+#[test]
+fn materials_mix_per_contact() {
+    let default = PhysicsMaterial::default();
+
+    assert_eq!(PhysicsMaterial::ICE.mix(default).friction, 0.0);
+    assert_eq!(PhysicsMaterial::STICKY.mix(default).friction, 2.0);
+    assert_eq!(
+        default.mix(PhysicsMaterial::BOUNCY).restitution,
+        PhysicsMaterial::BOUNCY.restitution
+    );
+}
+
+#[test]
+fn icy_crate_slides_off_walking_player() {
+    let mut sim = Simulation::new();
+
+    let crate_entity = sim.spawn(|commands| {
+        spawn_ground(commands, Handle::default(), Vec2::new(800., 50.), t(0., 0.));
+        spawn_player(
+            commands,
+            Handle::default(),
+            Vec2::new(50., 100.),
+            t(0., 75.),
+        );
+        let crate_entity = spawn_crate(
+            commands,
+            Handle::default(),
+            Vec2::new(50., 50.),
+            t(0., 150.),
+        );
+        commands.entity(crate_entity).insert(PhysicsMaterial::ICE);
+        crate_entity
+    });
+
+    sim.run(30, &[KeyCode::D]);
+
+    let x = sim.transform(crate_entity).translation.x;
+    assert!(x.abs() < 1.0, "crate at x = {}", x);
+}
+
+#[test]
+fn crate_bounces_off_bouncy_ground() {
+    let mut sim = Simulation::new();
+
+    let crate_entity = sim.spawn(|commands| {
+        let ground = spawn_ground(commands, Handle::default(), Vec2::new(200., 50.), t(0., 0.));
+        commands.entity(ground).insert(PhysicsMaterial::BOUNCY);
+        spawn_crate(commands, Handle::default(), Vec2::new(50., 50.), t(0., 60.))
+    });
+
+    let mut heights = vec![];
+    for _ in 0..30 {
+        sim.tick(&[]);
+        heights.push(sim.transform(crate_entity).translation.y);
+    }
+
+    assert!(
+        heights.windows(2).any(|pair| pair[1] > pair[0]),
+        "crate never rose: {:?}",
+        heights
+    );
+}
+
+#[test]
+fn contacts_follow_rotation() {
+    for &(degrees, touching) in [(0.0_f32, false), (45.0, true)].iter() {
+        let mut sim = Simulation::new();
+
+        let (diamond, above) = sim.spawn(|commands| {
+            let diamond = spawn_ground(
+                commands,
+                Handle::default(),
+                Vec2::new(50., 50.),
+                Transform {
+                    rotation: Quat::from_rotation_z(degrees.to_radians()),
+                    ..t(0., 0.)
+                },
+            );
+            let above = spawn_ground(commands, Handle::default(), Vec2::new(50., 50.), t(0., 58.));
+            (diamond, above)
+        });
+
+        sim.tick(&[]);
+
+        let contacts = sim.world.get_resource::<Contacts>().unwrap();
+        assert_eq!(
+            contacts.contains(diamond, above),
+            touching,
+            "rotated {} degrees",
+            degrees
+        );
+    }
+}
+
+#[test]
+fn tilted_crate_settles_flat() {
+    let mut sim = Simulation::new();
+
+    let crate_entity = sim.spawn(|commands| {
+        spawn_ground(commands, Handle::default(), Vec2::new(200., 50.), t(0., 0.));
+        spawn_crate(
+            commands,
+            Handle::default(),
+            Vec2::new(50., 50.),
+            Transform {
+                rotation: Quat::from_rotation_z(0.5),
+                ..t(0., 70.)
+            },
+        )
+    });
+
+    sim.run(240, &[]);
+
+    let xform = sim.transform(crate_entity);
+    assert!(
+        angle(&xform).abs() < 0.01,
+        "crate at {} radians",
+        angle(&xform)
+    );
+    assert!(
+        (xform.translation.y - 50.0).abs() < 1.0,
+        "crate at y = {}",
+        xform.translation.y
+    );
+}
+
+fn ramp(commands: &mut Commands) -> Entity {
+    spawn_ramp(
+        commands,
+        Handle::default(),
+        Vec2::new(200., 100.),
+        Facing::Right,
+        t(0., 50.),
+    )
+}
+
+#[test]
+fn player_walks_up_ramp() {
+    let mut sim = Simulation::new();
+
+    let player = sim.spawn(|commands| {
+        spawn_ground(
+            commands,
+            Handle::default(),
+            Vec2::new(300., 50.),
+            t(-250., -25.),
+        );
+        ramp(commands);
+        spawn_player(
+            commands,
+            Handle::default(),
+            Vec2::new(50., 100.),
+            t(-200., 50.),
+        )
+    });
+
+    sim.run(150, &[KeyCode::D]);
+
+    let translation = sim.transform(player).translation;
+    assert!(translation.x > -60.0, "player at x = {}", translation.x);
+    assert!(translation.y > 70.0, "player at y = {}", translation.y);
+}
+
+#[test]
+fn only_slippery_crates_slide_down_shallow_ramp() {
+    for &(material, slides) in [
+        (PhysicsMaterial::default(), false),
+        (PhysicsMaterial::ICE, true),
+    ]
+    .iter()
+    {
+        let mut sim = Simulation::new();
+
+        let crate_entity = sim.spawn(|commands| {
+            ramp(commands);
+            let crate_entity = spawn_crate(
+                commands,
+                Handle::default(),
+                Vec2::new(50., 50.),
+                t(0., 100.),
+            );
+            commands.entity(crate_entity).insert(material);
+            crate_entity
+        });
+
+        sim.run(90, &[]);
+
+        let x = sim.transform(crate_entity).translation.x;
+        if slides {
+            assert!(x < -30.0, "{:?} crate at x = {}", material, x);
+        } else {
+            assert!(x.abs() < 10.0, "{:?} crate at x = {}", material, x);
+        }
+    }
+}
+
+#[test]
+fn player_climbs_up_through_platform_and_stands_on_it() {
+    let mut sim = Simulation::new();
+
+    let player = sim.spawn(|commands| {
+        spawn_ground(commands, Handle::default(), Vec2::new(400., 50.), t(0., 0.));
+        spawn_ladder(
+            commands,
+            Handle::default(),
+            t(0., 200.),
+            Vec2::new(50., 400.),
+        );
+        spawn_platform(
+            commands,
+            Handle::default(),
+            Vec2::new(200., 10.),
+            t(0., 150.),
+        );
+        spawn_player(
+            commands,
+            Handle::default(),
+            Vec2::new(50., 100.),
+            t(0., 75.),
+        )
+    });
+
+    sim.run(140, &[KeyCode::W]);
+    let climbed = sim.transform(player).translation.y;
+    assert!(climbed > 205.0, "player only climbed to y = {}", climbed);
+
+    // step off the ladder and drop onto the platform
+    sim.run(30, &[KeyCode::D]);
+    sim.run(30, &[]);
+    let y = sim.transform(player).translation.y;
+    assert!((y - 205.0).abs() < 1.0, "player at y = {}", y);
+}
+
+#[test]
+fn conveyor_carries_crates_only_while_running() {
+    for &(state, moves) in [
+        (EscalatorState::Running, true),
+        (EscalatorState::Stopped, false),
+    ]
+    .iter()
+    {
+        let mut sim = Simulation::new();
+
+        let crate_entity = sim.spawn(|commands| {
+            spawn_conveyor(
+                commands,
+                Handle::default(),
+                Vec2::new(400., 50.),
+                t(0., 0.),
+                Conveyor {
+                    facing: Facing::Right,
+                    speed: 1.0,
+                    state,
+                },
+            );
+            spawn_crate(commands, Handle::default(), Vec2::new(50., 50.), t(0., 50.))
+        });
+
+        sim.run(30, &[]);
+
+        let x = sim.transform(crate_entity).translation.x;
+        if moves {
+            assert!((x - 30. * DELTA).abs() < 1.0, "crate at x = {}", x);
+        } else {
+            assert!(x.abs() < 1e-3, "crate at x = {}", x);
+        }
+    }
+}
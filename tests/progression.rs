@@ -0,0 +1,80 @@
+use bevy::prelude::*;
+use staircases::*;
+
+fn t(x: f32, y: f32) -> Transform {
+    Transform::from_translation(Vec3::new(x, y, 0.0))
+}
+
+fn goal_reached(sim: &Simulation) -> bool {
+    sim.world.get_resource::<GoalReached>().expect("goal").0
+}
+
+#[test]
+fn walking_into_exit_wins() {
+    let mut sim = Simulation::new();
+
+    sim.spawn(|commands| {
+        spawn_ground(commands, Handle::default(), Vec2::new(800., 50.), t(0., 0.));
+        spawn_exit(
+            commands,
+            Handle::default(),
+            Vec2::new(50., 100.),
+            t(150., 75.),
+        );
+        spawn_player(
+            commands,
+            Handle::default(),
+            Vec2::new(50., 100.),
+            t(0., 75.),
+        );
+    });
+
+    sim.run(1, &[]);
+    assert!(!goal_reached(&sim));
+
+    sim.run(120, &[KeyCode::D]);
+    assert!(goal_reached(&sim));
+}
+
+#[test]
+fn every_target_pad_needs_a_crate() {
+    let mut sim = Simulation::new();
+
+    sim.spawn(|commands| {
+        spawn_ground(commands, Handle::default(), Vec2::new(800., 50.), t(0., 0.));
+        spawn_crate(commands, Handle::default(), Vec2::new(50., 50.), t(0., 50.));
+        spawn_target_pad(commands, Handle::default(), Vec2::new(50., 10.), t(0., 25.));
+        spawn_target_pad(
+            commands,
+            Handle::default(),
+            Vec2::new(50., 10.),
+            t(200., 25.),
+        );
+    });
+
+    sim.run(1, &[]);
+    assert!(!goal_reached(&sim));
+
+    sim.spawn(|commands| {
+        spawn_crate(
+            commands,
+            Handle::default(),
+            Vec2::new(50., 50.),
+            t(200., 50.),
+        );
+    });
+
+    sim.run(1, &[]);
+    assert!(goal_reached(&sim));
+}
+
+#[test]
+fn campaign_starts_over_after_last_level() {
+    let level = Level::from_ron(include_str!("../assets/levels/01.ron")).expect("level");
+    let mut campaign = Campaign::new(vec![level.clone(), level]);
+
+    assert!(campaign.advance());
+    assert_eq!(campaign.current, 1);
+    assert!(!campaign.advance());
+    assert_eq!(campaign.current, 0);
+}
@@ -0,0 +1,71 @@
+use bevy::prelude::*;
+use staircases::*;
+
+fn load(path: &str) -> Simulation {
+    let level =
+        Level::from_ron(&std::fs::read_to_string(path).expect("level file")).expect("level");
+
+    let mut sim = Simulation::new();
+    sim.spawn(|commands| spawn_level(commands, &level, &LevelMaterials::default()))
+        .expect("level wires");
+    sim
+}
+
+fn bodies(sim: &mut Simulation) -> Vec<Vec2> {
+    let world = &mut sim.world;
+    body_positions(
+        world
+            .query_filtered::<(&InLevel, &Transform), With<Velocity>>()
+            .iter(world),
+    )
+}
+
+#[test]
+fn recorded_run_replays_exactly() {
+    let mut sim = load("assets/levels/01.ron");
+    sim.world
+        .insert_resource(ReplayMode::Record(Replay::new("assets/levels/01.ron")));
+
+    sim.run(20, &[KeyCode::A]);
+    sim.run(5, &[]);
+    sim.run(20, &[KeyCode::D, KeyCode::W]);
+    let recorded = bodies(&mut sim);
+
+    let replay = match sim.world.remove_resource::<ReplayMode>() {
+        Some(ReplayMode::Record(replay)) => replay,
+        _ => panic!("not recording"),
+    };
+    assert_eq!(replay.ticks(), 45);
+    assert_eq!(replay.inputs.len(), 3);
+
+    let replay = Replay::from_ron(&replay.to_ron().expect("serialize")).expect("deserialize");
+    let mut sim = load(&replay.level);
+    sim.play(&replay);
+    assert_eq!(bodies(&mut sim), recorded);
+}
+
+// golden replays: every file in tests/replays must end where it says it does
+#[test]
+fn saved_replays_match_their_recorded_outcome() {
+    for entry in std::fs::read_dir("tests/replays").expect("replay dir") {
+        let path = entry.expect("replay entry").path();
+        let replay = Replay::from_ron(&std::fs::read_to_string(&path).expect("replay file"))
+            .expect("replay");
+        let expected = replay.expected.as_ref().expect("expected positions");
+
+        let mut sim = load(&replay.level);
+        sim.play(&replay);
+        let actual = bodies(&mut sim);
+
+        assert_eq!(actual.len(), expected.len(), "{}", path.display());
+        for (actual, expected) in actual.iter().zip(expected) {
+            assert!(
+                (*actual - *expected).length() < 1e-3,
+                "{}: body at {} rather than {}",
+                path.display(),
+                actual,
+                expected
+            );
+        }
+    }
+}
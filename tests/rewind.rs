@@ -0,0 +1,212 @@
+use bevy::{app::Events, prelude::*};
+use parry2d::shape::ConvexPolygon;
+use staircases::*;
+
+fn t(x: f32, y: f32) -> Transform {
+    Transform::from_translation(Vec3::new(x, y, 0.0))
+}
+
+#[test]
+fn rewinding_restores_player_and_escalator_steps() {
+    let mut sim = Simulation::new();
+
+    let escalator_xform = t(-200., 125.);
+    let (player, steps) = sim.spawn(|commands| {
+        spawn_ground(commands, Handle::default(), Vec2::new(800., 50.), t(0., 0.));
+        let escalator = spawn_escalator(
+            commands,
+            Handle::default(),
+            escalator_xform,
+            Escalator {
+                length: 200.,
+                facing: Facing::Left,
+                travel: Travel::Up,
+                speed: 1.0,
+                state: EscalatorState::Running,
+            },
+        );
+        let steps = steps(escalator_xform, 200., 50., Facing::Left)
+            .into_iter()
+            .map(|(transform, track_position, track_length)| {
+                spawn_step(
+                    commands,
+                    Handle::default(),
+                    escalator,
+                    transform,
+                    50.,
+                    track_position,
+                    track_length,
+                )
+            })
+            .collect::<Vec<_>>();
+
+        let player = spawn_player(
+            commands,
+            Handle::default(),
+            Vec2::new(50., 100.),
+            t(100., 75.),
+        );
+        (player, steps)
+    });
+
+    let state = |sim: &Simulation| {
+        let mut entities = steps.clone();
+        entities.push(player);
+        entities
+            .into_iter()
+            .map(|entity| {
+                (
+                    sim.transform(entity).translation,
+                    sim.world.get::<Track>(entity).map(|track| track.position),
+                )
+            })
+            .collect::<Vec<_>>()
+    };
+
+    sim.run(20, &[KeyCode::D]);
+    let checkpoint = state(&sim);
+
+    sim.run(10, &[KeyCode::D]);
+    let end = state(&sim);
+    assert_ne!(checkpoint, end);
+
+    sim.run(10, &[REWIND_KEY]);
+    assert_eq!(state(&sim), checkpoint);
+
+    // resuming replays the same ticks
+    sim.run(10, &[KeyCode::D]);
+    assert_eq!(state(&sim), end);
+}
+
+#[test]
+fn rewinding_a_lever_press_closes_its_door() {
+    let mut sim = Simulation::new();
+
+    let (lever, door) = sim.spawn(|commands| {
+        spawn_ground(commands, Handle::default(), Vec2::new(800., 50.), t(0., 0.));
+        spawn_player(
+            commands,
+            Handle::default(),
+            Vec2::new(50., 100.),
+            t(0., 75.),
+        );
+        let lever = spawn_lever(commands, Handle::default(), Vec2::new(20., 50.), t(0., 50.));
+
+        let door = spawn_door(
+            commands,
+            Handle::default(),
+            Vec2::new(20., 100.),
+            t(200., 75.),
+        );
+        commands.entity(door).insert(Powered {
+            sources: vec![lever],
+        });
+        (lever, door)
+    });
+
+    let is_open = |sim: &Simulation| sim.world.get::<ConvexPolygon>(door).is_none();
+
+    sim.run(1, &[]);
+    sim.run(1, &[LEVER_KEY]);
+    assert!(is_open(&sim));
+
+    // the door shuts on the tick the press is undone, not the one after
+    sim.run(1, &[REWIND_KEY]);
+    assert!(!is_open(&sim));
+    assert!(!sim.world.get::<Door>(door).expect("door").open);
+    assert!(!sim.world.get::<Switch>(lever).expect("lever").on);
+    assert!(!sim.world.get::<Lever>(lever).expect("lever").held);
+}
+
+#[test]
+fn rewinding_a_lever_press_stops_its_conveyor() {
+    let mut sim = Simulation::new();
+
+    let conveyor = sim.spawn(|commands| {
+        spawn_ground(commands, Handle::default(), Vec2::new(800., 50.), t(0., 0.));
+        spawn_player(
+            commands,
+            Handle::default(),
+            Vec2::new(50., 100.),
+            t(0., 75.),
+        );
+        let lever = spawn_lever(commands, Handle::default(), Vec2::new(20., 50.), t(0., 50.));
+
+        let conveyor = spawn_conveyor(
+            commands,
+            Handle::default(),
+            Vec2::new(200., 50.),
+            t(0., 300.),
+            Conveyor {
+                facing: Facing::Left,
+                speed: 1.0,
+                state: EscalatorState::Running,
+            },
+        );
+        commands.entity(conveyor).insert(Powered {
+            sources: vec![lever],
+        });
+        conveyor
+    });
+
+    let state = |sim: &Simulation| sim.world.get::<Conveyor>(conveyor).expect("conveyor").state;
+
+    sim.run(1, &[]);
+    sim.run(1, &[LEVER_KEY]);
+    assert_eq!(state(&sim), EscalatorState::Running);
+
+    sim.run(1, &[REWIND_KEY]);
+    assert_eq!(state(&sim), EscalatorState::Stopped);
+}
+
+#[test]
+fn rewinding_a_landing_doesnt_end_the_contact() {
+    let mut sim = Simulation::new();
+
+    let (ground, crate_) = sim.spawn(|commands| {
+        let ground = spawn_ground(commands, Handle::default(), Vec2::new(200., 50.), t(0., 0.));
+        let crate_ = spawn_crate(
+            commands,
+            Handle::default(),
+            Vec2::new(50., 50.),
+            t(0., 100.),
+        );
+        (ground, crate_)
+    });
+    let pair = (ground.min(crate_), ground.max(crate_));
+
+    let mut reader = sim
+        .world
+        .get_resource::<Events<CollisionEvent>>()
+        .expect("collision events")
+        .get_reader();
+    let mut seen = vec![];
+    let mut run = |sim: &mut Simulation, ticks: usize, keys: &[KeyCode]| {
+        for _ in 0..ticks {
+            sim.tick(keys);
+
+            let events = sim
+                .world
+                .get_resource::<Events<CollisionEvent>>()
+                .expect("collision events");
+            for event in reader.iter(events) {
+                match *event {
+                    CollisionEvent::Started(a, b) if (a, b) == pair => seen.push("started"),
+                    CollisionEvent::Persisted(a, b) if (a, b) == pair => seen.push("persisted"),
+                    CollisionEvent::Ended(a, b) if (a, b) == pair => seen.push("ended"),
+                    _ => {}
+                }
+            }
+        }
+    };
+
+    // the crate falls, lands and settles
+    run(&mut sim, 60, &[]);
+    // back to before it landed, then down again
+    run(&mut sim, 59, &[REWIND_KEY]);
+    run(&mut sim, 60, &[]);
+
+    // the jump back in time isn't the crate leaving the ground
+    assert!(seen.contains(&"started"));
+    assert!(!seen.contains(&"ended"));
+}
@@ -0,0 +1,71 @@
+use std::{
+    fmt,
+    sync::{Arc, Mutex},
+};
+
+use bevy::{
+    ecs::schedule::ReportExecutionOrderAmbiguities,
+    utils::tracing::{
+        self,
+        field::{Field, Visit},
+        span, Event, Metadata, Subscriber,
+    },
+};
+use staircases::*;
+
+/// Keeps the message of every event logged while it's the default subscriber.
+#[derive(Clone, Default)]
+struct Messages(Arc<Mutex<Vec<String>>>);
+
+struct Message<'a>(&'a mut String);
+
+impl Visit for Message<'_> {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "message" {
+            *self.0 = format!("{:?}", value);
+        }
+    }
+}
+
+impl Subscriber for Messages {
+    fn enabled(&self, _: &Metadata) -> bool {
+        true
+    }
+
+    fn new_span(&self, _: &span::Attributes) -> span::Id {
+        span::Id::from_u64(1)
+    }
+
+    fn record(&self, _: &span::Id, _: &span::Record) {}
+
+    fn record_follows_from(&self, _: &span::Id, _: &span::Id) {}
+
+    fn event(&self, event: &Event) {
+        let mut message = String::new();
+        event.record(&mut Message(&mut message));
+        self.0.lock().expect("messages").push(message);
+    }
+
+    fn enter(&self, _: &span::Id) {}
+
+    fn exit(&self, _: &span::Id) {}
+}
+
+// Bevy is free to run conflicting systems in either order unless told
+// otherwise, and may pick differently each time the stage is built, so any
+// ambiguity can make the same input give a different trajectory
+#[test]
+fn physics_systems_run_in_one_order() {
+    let messages = Messages::default();
+
+    let mut sim = Simulation::new();
+    sim.world.insert_resource(ReportExecutionOrderAmbiguities);
+    tracing::subscriber::with_default(messages.clone(), || sim.tick(&[]));
+
+    let messages = messages.0.lock().expect("messages");
+    let ambiguities: Vec<_> = messages
+        .iter()
+        .filter(|message| message.contains("ambiguities"))
+        .collect();
+    assert!(ambiguities.is_empty(), "{:#?}", ambiguities);
+}
@@ -0,0 +1,195 @@
+use bevy::prelude::*;
+use parry2d::shape::ConvexPolygon;
+use staircases::*;
+
+fn t(x: f32, y: f32) -> Transform {
+    Transform::from_translation(Vec3::new(x, y, 0.0))
+}
+
+fn escalator() -> Escalator {
+    Escalator {
+        length: 200.,
+        facing: Facing::Left,
+        travel: Travel::Up,
+        speed: 1.0,
+        state: EscalatorState::Running,
+    }
+}
+
+#[test]
+fn escalator_runs_only_while_its_plate_is_held() {
+    let mut sim = Simulation::new();
+
+    let (held, empty) = sim.spawn(|commands| {
+        spawn_ground(commands, Handle::default(), Vec2::new(800., 50.), t(0., 0.));
+        spawn_crate(commands, Handle::default(), Vec2::new(50., 50.), t(0., 50.));
+
+        let held_plate =
+            spawn_pressure_plate(commands, Handle::default(), Vec2::new(50., 10.), t(0., 25.));
+        let empty_plate = spawn_pressure_plate(
+            commands,
+            Handle::default(),
+            Vec2::new(50., 10.),
+            t(200., 25.),
+        );
+
+        let held = spawn_escalator(commands, Handle::default(), t(-300., 500.), escalator());
+        commands.entity(held).insert(Powered {
+            sources: vec![held_plate],
+        });
+        let empty = spawn_escalator(commands, Handle::default(), t(300., 500.), escalator());
+        commands.entity(empty).insert(Powered {
+            sources: vec![empty_plate],
+        });
+
+        (held, empty)
+    });
+
+    sim.run(1, &[]);
+
+    let state = |entity| sim.world.get::<Escalator>(entity).expect("escalator").state;
+    assert_eq!(state(held), EscalatorState::Running);
+    assert_eq!(state(empty), EscalatorState::Stopped);
+}
+
+#[test]
+fn powered_escalator_keeps_its_authored_direction() {
+    let mut sim = Simulation::new();
+
+    let escalator = sim.spawn(|commands| {
+        spawn_ground(commands, Handle::default(), Vec2::new(800., 50.), t(0., 0.));
+        spawn_crate(commands, Handle::default(), Vec2::new(50., 50.), t(0., 50.));
+        let plate =
+            spawn_pressure_plate(commands, Handle::default(), Vec2::new(50., 10.), t(0., 25.));
+
+        let escalator = spawn_escalator(
+            commands,
+            Handle::default(),
+            t(-300., 500.),
+            Escalator {
+                state: EscalatorState::Reversing,
+                ..escalator()
+            },
+        );
+        commands.entity(escalator).insert(Powered {
+            sources: vec![plate],
+        });
+        escalator
+    });
+
+    sim.run(1, &[]);
+
+    let state = sim
+        .world
+        .get::<Escalator>(escalator)
+        .expect("escalator")
+        .state;
+    assert_eq!(state, EscalatorState::Reversing);
+}
+
+#[test]
+fn lever_toggles_door_once_per_press() {
+    let mut sim = Simulation::new();
+
+    let door = sim.spawn(|commands| {
+        spawn_ground(commands, Handle::default(), Vec2::new(800., 50.), t(0., 0.));
+        spawn_player(
+            commands,
+            Handle::default(),
+            Vec2::new(50., 100.),
+            t(0., 75.),
+        );
+        let lever = spawn_lever(commands, Handle::default(), Vec2::new(20., 50.), t(0., 50.));
+
+        let door = spawn_door(
+            commands,
+            Handle::default(),
+            Vec2::new(20., 100.),
+            t(200., 75.),
+        );
+        commands.entity(door).insert(Powered {
+            sources: vec![lever],
+        });
+        door
+    });
+
+    let is_open = |sim: &Simulation| sim.world.get::<ConvexPolygon>(door).is_none();
+
+    sim.run(1, &[]);
+    assert!(!is_open(&sim));
+
+    sim.run(5, &[LEVER_KEY]);
+    assert!(is_open(&sim));
+
+    sim.run(1, &[]);
+    sim.run(1, &[LEVER_KEY]);
+    assert!(!is_open(&sim));
+}
+
+#[test]
+fn conveyor_stops_without_power() {
+    let mut sim = Simulation::new();
+
+    let conveyor = sim.spawn(|commands| {
+        let plate = spawn_pressure_plate(
+            commands,
+            Handle::default(),
+            Vec2::new(50., 10.),
+            t(300., 25.),
+        );
+        let conveyor = spawn_conveyor(
+            commands,
+            Handle::default(),
+            Vec2::new(400., 50.),
+            t(0., 0.),
+            Conveyor {
+                facing: Facing::Left,
+                speed: 1.0,
+                state: EscalatorState::Running,
+            },
+        );
+        commands.entity(conveyor).insert(Powered {
+            sources: vec![plate],
+        });
+        conveyor
+    });
+
+    sim.run(1, &[]);
+
+    let conveyor = sim.world.get::<Conveyor>(conveyor).expect("conveyor");
+    assert_eq!(conveyor.state, EscalatorState::Stopped);
+    assert_eq!(conveyor.surface_velocity(), Vec2::ZERO);
+}
+
+#[test]
+fn powered_conveyor_keeps_its_authored_direction() {
+    let mut sim = Simulation::new();
+
+    let conveyor = sim.spawn(|commands| {
+        spawn_ground(commands, Handle::default(), Vec2::new(800., 50.), t(0., 0.));
+        spawn_crate(commands, Handle::default(), Vec2::new(50., 50.), t(0., 50.));
+        let plate =
+            spawn_pressure_plate(commands, Handle::default(), Vec2::new(50., 10.), t(0., 25.));
+
+        let conveyor = spawn_conveyor(
+            commands,
+            Handle::default(),
+            Vec2::new(400., 50.),
+            t(0., 300.),
+            Conveyor {
+                facing: Facing::Left,
+                speed: 1.0,
+                state: EscalatorState::Reversing,
+            },
+        );
+        commands.entity(conveyor).insert(Powered {
+            sources: vec![plate],
+        });
+        conveyor
+    });
+
+    sim.run(1, &[]);
+
+    let state = sim.world.get::<Conveyor>(conveyor).expect("conveyor").state;
+    assert_eq!(state, EscalatorState::Reversing);
+}